@@ -1,16 +1,23 @@
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use nih_plug::editor::Editor;
-use nih_plug::prelude::{GuiContext, ParamSetter};
+use nih_plug::prelude::{AsyncExecutor, GuiContext, ParamSetter};
 use nih_plug_vizia::{assets, create_vizia_editor, ViziaState, ViziaTheming};
 use nih_plug_vizia::vizia::prelude::*;
 use nih_plug_vizia::widgets::ResizeHandle;
 use crate::gui::events::{add_item, ControlEvent};
 use crate::gui::ui_parts::envelope_control_list::EnvelopeControlList;
+use crate::gui::ui_parts::filter_controls::FilterControls;
+use crate::gui::ui_parts::lfo_control_list::LfoControlList;
+use crate::gui::ui_parts::midi_source_list::MidiSourceList;
 use crate::gui::ui_parts::oscillator_control_list::OscillatorControlList;
 use crate::gui::ui_parts::visualiser::Visualiser;
-use crate::SynthParams;
+use crate::gui::ui_parts::voice_controls::VoiceControls;
+use crate::{MAX_HARMONICS, Synth, SynthParams, Task};
+use crate::process::additive::Harmonics;
+use crate::process::modulation::ModulationRouting;
 use crate::process::visual_data::VisualData;
+use crate::utils::swap_slot::SwapSlot;
 
 mod components;
 mod ui_parts;
@@ -20,10 +27,14 @@ pub(crate) mod events;
 pub struct GuiData {
     params: Arc<SynthParams>,
     visual_data: Arc<Mutex<triple_buffer::Output<VisualData>>>,
+    routing: Arc<SwapSlot<ModulationRouting>>,
+    harmonics: Arc<SwapSlot<Harmonics>>,
+    async_executor: AsyncExecutor<Synth>,
     gui_context: Arc<dyn GuiContext>,
     // TODO data structure to generalise this?
     max_oscillators: Arc<AtomicBool>,
     max_envelopes: Arc<AtomicBool>,
+    max_lfos: Arc<AtomicBool>,
 }
 
 impl Model for GuiData {
@@ -45,15 +56,97 @@ impl Model for GuiData {
                 self.max_envelopes.store(false, Ordering::Relaxed);
             }
 
-            ControlEvent::SetEnvelopeTarget(envelope_idx, target_idx, new_target) => {
-                self.params.envelope_params[*envelope_idx]
-                    .targets.lock().expect("Cannot lock envelope targets")
+            ControlEvent::AddLfo => {
+                add_item(&self.params.lfo_params, setter, self.max_lfos.clone());
+            }
+            ControlEvent::RemoveLfo => {
+                self.max_lfos.store(false, Ordering::Relaxed);
+            }
+
+            ControlEvent::SetTarget(source, target_idx, new_target) => {
+                source.targets(&self.params)
+                    .lock().expect("Cannot lock modulation targets")
                     .targets[*target_idx].0 = *new_target;
+                self.send_routing();
             }
-            ControlEvent::SetEnvelopeTargetDepth(envelope_idx, target_idx, new_depth) => {
-                self.params.envelope_params[*envelope_idx]
-                    .targets.lock().expect("Cannot lock envelope targets")
+            ControlEvent::SetTargetDepth(source, target_idx, new_depth) => {
+                source.targets(&self.params)
+                    .lock().expect("Cannot lock modulation targets")
                     .targets[*target_idx].1 = *new_depth;
+                self.send_routing();
+            }
+            // The targets list has already updated the parameters
+            ControlEvent::AddTarget | ControlEvent::RemoveTarget(_) => {
+                self.send_routing();
+            }
+            ControlEvent::LoadWavetable(oscillator_idx) => {
+                let path = rfd::FileDialog::new()
+                    .add_filter("Wavetable", &["wav"])
+                    .pick_file();
+
+                // Decoding and building the mipmaps can take a while, so it is done in the background
+                if let Some(path) = path {
+                    self.async_executor.execute_background(
+                        Task::LoadWavetable(*oscillator_idx, path.to_string_lossy().into_owned())
+                    );
+                }
+            }
+            ControlEvent::LoadSample(oscillator_idx) => {
+                let path = rfd::FileDialog::new()
+                    .add_filter("Audio", &["wav", "flac"])
+                    .pick_file();
+
+                // Decoding can take a while, so it is done in the background
+                if let Some(path) = path {
+                    self.async_executor.execute_background(
+                        Task::LoadSample(*oscillator_idx, path.to_string_lossy().into_owned())
+                    );
+                }
+            }
+            ControlEvent::ShiftCc(cc_idx, amount) => {
+                {
+                    let mut cc = self.params.cc_params[*cc_idx].cc.lock().expect("Cannot lock CC number");
+                    *cc = (*cc as i16 + *amount as i16).clamp(0, 127) as u8;
+                }
+                self.send_routing();
+            }
+            ControlEvent::SetHarmonic(oscillator_idx, harmonic, amplitude) => {
+                {
+                    let mut harmonics = self.params.harmonics.lock().expect("Cannot lock harmonics");
+                    let harmonics = &mut harmonics[*oscillator_idx];
+                    // Older or edited states might not contain every harmonic
+                    harmonics.resize(MAX_HARMONICS, 0.0);
+                    harmonics[*harmonic] = *amplitude;
+                }
+                self.harmonics.send(Harmonics::from_params(&self.params));
+            }
+            ControlEvent::SetFmAlgorithm(algorithm) => {
+                let matrix = algorithm.matrix();
+                let mut all_enabled = true;
+                for (i, osc_params) in self.params.oscillator_params.iter().enumerate() {
+                    for (fm_params, index) in osc_params.fm.iter().zip(matrix[i]) {
+                        setter.begin_set_parameter(&fm_params.index);
+                        setter.set_parameter(&fm_params.index, index);
+                        setter.end_set_parameter(&fm_params.index);
+                    }
+                    let output = algorithm.outputs()[i];
+                    setter.begin_set_parameter(&osc_params.output);
+                    setter.set_parameter(&osc_params.output, output);
+                    setter.end_set_parameter(&osc_params.output);
+
+                    // Enable the oscillators that the algorithm uses
+                    let modulates = matrix.iter().any(|fm| fm[i] != 0.0);
+                    if (output || modulates) && !osc_params.enabled.value() {
+                        setter.begin_set_parameter(&osc_params.enabled);
+                        setter.set_parameter(&osc_params.enabled, true);
+                        setter.end_set_parameter(&osc_params.enabled);
+                    } else if !osc_params.enabled.value() {
+                        all_enabled = false;
+                    }
+                }
+                if all_enabled {
+                    self.max_oscillators.store(true, Ordering::Relaxed);
+                }
             }
             _ => {}
         });
@@ -61,14 +154,24 @@ impl Model for GuiData {
     }
 }
 
+impl GuiData {
+    /// Send the changed modulation targets to the audio thread
+    fn send_routing(&self) {
+        self.routing.send(ModulationRouting::from_params(&self.params));
+    }
+}
+
 pub(crate) fn default_state() -> Arc<ViziaState> {
-    ViziaState::new(|| (700, 550))
+    ViziaState::new(|| (1150, 700))
 }
 
 pub(crate) fn create(
     params: Arc<SynthParams>,
     editor_state: Arc<ViziaState>,
     visual_data: Arc<Mutex<triple_buffer::Output<VisualData>>>,
+    routing: Arc<SwapSlot<ModulationRouting>>,
+    harmonics: Arc<SwapSlot<Harmonics>>,
+    async_executor: AsyncExecutor<Synth>,
 ) -> Option<Box<dyn Editor>> {
     create_vizia_editor(
         editor_state,
@@ -83,9 +186,13 @@ pub(crate) fn create(
             GuiData {
                 params: params.clone(),
                 visual_data: visual_data.clone(),
+                routing: routing.clone(),
+                harmonics: harmonics.clone(),
+                async_executor: async_executor.clone(),
                 gui_context: gui_cx,
                 max_oscillators: Arc::new(AtomicBool::new(false)),
                 max_envelopes: Arc::new(AtomicBool::new(false)),
+                max_lfos: Arc::new(AtomicBool::new(false)),
             }.build(cx);
 
             let max_oscillators = GuiData::max_oscillators.map(|m|{
@@ -94,6 +201,9 @@ pub(crate) fn create(
             let max_envelopes = GuiData::max_envelopes.map(|m|{
                 m.load(Ordering::Relaxed)
             });
+            let max_lfos = GuiData::max_lfos.map(|m|{
+                m.load(Ordering::Relaxed)
+            });
 
             ResizeHandle::new(cx);
 
@@ -109,8 +219,24 @@ pub(crate) fn create(
                 HStack::new(cx, |cx| {
                     OscillatorControlList::new(cx, max_oscillators);
 
+                    VStack::new(cx, |cx| {
+                        FilterControls::new(cx);
+
+                        VoiceControls::new(cx);
+                    })
+                        .row_between(Pixels(10.0))
+                        .width(Pixels(180.0));
+
                     EnvelopeControlList::new(cx, max_envelopes);
 
+                    VStack::new(cx, |cx| {
+                        LfoControlList::new(cx, max_lfos);
+
+                        MidiSourceList::new(cx);
+                    })
+                        .row_between(Pixels(10.0))
+                        .width(Pixels(220.0));
+
                     Visualiser::new(cx);
                 }).col_between(Pixels(20.0));
             }).child_space(Stretch(1.0))
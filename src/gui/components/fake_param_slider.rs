@@ -14,6 +14,8 @@ pub struct FakeParamSlider<L: Lens> {
     lens: L,
     on_changing: Option<Box<dyn Fn(&mut EventContext, f32)>>,
     default_value: f32,
+    min: f32,
+    max: f32,
 
     /// Will be set to `true` when the field gets Alt+Click'ed which will replace the label with a
     /// text box.
@@ -57,14 +59,16 @@ pub struct GranularDragStatus {
 impl<L> FakeParamSlider<L>
     where L: Lens<Target=f32>
 {
-    /// Creates a new [`FakeParamSlider`] for the given lens value. Parameter changes are
-    /// handled by the `on_changing` handle extension.
+    /// Creates a new [`FakeParamSlider`] for the given lens value, which ranges from `min` to
+    /// `max`. Parameter changes are handled by the `on_changing` handle extension.
     ///
     /// See [`FakeParamSliderExt`] for additional options.
     pub fn new(
         cx: &mut Context,
         lens: L,
         default_value: f32,
+        min: f32,
+        max: f32,
     ) -> Handle<Self> {
         // We'll visualize the difference between the current value and the default value if the
         // default value lies somewhere in the middle and the parameter is continuous. Otherwise
@@ -73,6 +77,8 @@ impl<L> FakeParamSlider<L>
             lens: lens.clone(),
             on_changing: None,
             default_value,
+            min,
+            max,
 
             text_input_active: false,
             drag_active: false,
@@ -104,8 +110,8 @@ impl<L> FakeParamSlider<L>
                             lens.map(move |current_value| {
                                 Self::compute_fill_start_delta(
                                     style,
-                                    *current_value,
-                                    0.0, // TODO implement zero value
+                                    normalize(*current_value, min, max),
+                                    normalize(0.0, min, max),
                                 )
                             })
                         };
@@ -252,9 +258,18 @@ impl<L> FakeParamSlider<L>
     /// automation gesture.
     fn set_normalized_value_drag(&self, cx: &mut EventContext, normalized_value: f32) {
         if let Some(callback) = &self.on_changing {
-            (callback)(cx, normalized_value);
+            (callback)(cx, self.min + normalized_value * (self.max - self.min));
         }
     }
+
+    /// The current value, normalized to `[0, 1]`
+    fn normalized_value(&self, cx: &EventContext) -> f32 {
+        normalize(self.lens.get(cx), self.min, self.max)
+    }
+}
+
+fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    ((value - min) / (max - min)).clamp(0.0, 1.0)
 }
 
 impl<L> View for FakeParamSlider<L>
@@ -275,7 +290,7 @@ impl<L> View for FakeParamSlider<L>
             ParamSliderEvent::TextInput(string) => {
                 if let Ok(value) = string.parse::<f32>() {
                     if let Some(callback) = &self.on_changing {
-                        (callback)(cx, value);
+                        (callback)(cx, value.clamp(self.min, self.max));
                     }
                 }
 
@@ -315,7 +330,7 @@ impl<L> View for FakeParamSlider<L>
                     if cx.modifiers.shift() {
                         self.granular_drag_status = Some(GranularDragStatus {
                             starting_x_coordinate: cx.mouse.cursorx,
-                            starting_value: self.lens.get(cx),
+                            starting_value: self.normalized_value(cx),
                         });
                     } else {
                         self.granular_drag_status = None;
@@ -359,7 +374,7 @@ impl<L> View for FakeParamSlider<L>
                                 .granular_drag_status
                                 .get_or_insert_with(|| GranularDragStatus {
                                     starting_x_coordinate: *x,
-                                    starting_value: self.lens.get(cx),
+                                    starting_value: normalize(self.lens.get(cx), self.min, self.max),
                                 });
 
                         // These positions should be compensated for the DPI scale so it remains
@@ -389,9 +404,10 @@ impl<L> View for FakeParamSlider<L>
                 // position
                 if self.drag_active && self.granular_drag_status.is_some() {
                     self.granular_drag_status = None;
-                    if let Some(callback) = &self.on_changing {
-                        (callback)(cx, util::remap_current_entity_x_coordinate(cx, cx.mouse.cursorx));
-                    }
+                    self.set_normalized_value_drag(
+                        cx,
+                        util::remap_current_entity_x_coordinate(cx, cx.mouse.cursorx),
+                    );
                 }
             }
             WindowEvent::MouseScroll(_scroll_x, scroll_y) if self.use_scroll_wheel => {
@@ -404,9 +420,11 @@ impl<L> View for FakeParamSlider<L>
 
                                         let mut current_value = self.lens.get(cx);
 
+                    let step_size = (if use_finer_steps { STEP_SIZE_FINE } else { STEP_SIZE })
+                        * (self.max - self.min);
+
                     while self.scrolled_lines >= 1.0 {
-                        // TODO implement min and max value
-                        current_value += if use_finer_steps { STEP_SIZE_FINE } else { STEP_SIZE };
+                        current_value = (current_value + step_size).min(self.max);
                         if let Some(callback) = &self.on_changing {
                             (callback)(cx, current_value);
                         }
@@ -414,7 +432,7 @@ impl<L> View for FakeParamSlider<L>
                     }
 
                     while self.scrolled_lines <= -1.0 {
-                        current_value -= if use_finer_steps { STEP_SIZE_FINE } else { STEP_SIZE };
+                        current_value = (current_value - step_size).max(self.min);
                         if let Some(callback) = &self.on_changing {
                             (callback)(cx, current_value);
                         }
@@ -1,22 +1,32 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use nih_plug::prelude::ParamSetter;
-use crate::OSCILLATOR_AMOUNT;
 use crate::params::Enable;
-use crate::params::envelope_target::Target;
+use crate::params::envelope_target::{ModulationSource, Target};
+use crate::params::fm_params::FmAlgorithm;
 
 pub enum ControlEvent {
     AddOscillator,
     RemoveOscillator,
     AddEnvelope,
     RemoveEnvelope,
-    SetEnvelopeTarget(usize, usize, Target),
-    SetEnvelopeTargetDepth(usize, usize, f32),
-    AddEnvelopeTarget,
-    RemoveEnvelopeTarget(usize)
+    AddLfo,
+    RemoveLfo,
+    SetTarget(ModulationSource, usize, Target),
+    SetTargetDepth(ModulationSource, usize, f32),
+    AddTarget,
+    RemoveTarget(usize),
+    LoadWavetable(usize),
+    LoadSample(usize),
+    /// Change the CC number of a CC source by the given amount
+    ShiftCc(usize, i8),
+    /// Set the amplitude of a harmonic of an additive oscillator
+    SetHarmonic(usize, usize, f32),
+    /// Set the FM matrix and the oscillator outputs to a preset
+    SetFmAlgorithm(FmAlgorithm),
 }
 
-pub fn add_item<T>(params: &[T; OSCILLATOR_AMOUNT],
+pub fn add_item<T, const N: usize>(params: &[T; N],
                 setter: ParamSetter,
                 is_max: Arc<AtomicBool>,
 )   where T: Enable
@@ -1,3 +1,9 @@
 pub mod visualiser;
 pub mod oscillator_control_list;
 pub mod envelope_control_list;
+pub mod filter_controls;
+pub mod lfo_control_list;
+pub mod midi_source_list;
+pub mod target_dropdown;
+pub mod voice_controls;
+pub mod sub_controls;
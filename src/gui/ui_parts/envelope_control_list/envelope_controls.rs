@@ -6,7 +6,8 @@ use crate::gui::components::knob::ParamKnob;
 use crate::gui::components::param_button_wrapper::ParamButtonWrapper;
 use crate::gui::events::ControlEvent;
 use crate::gui::ui_parts::envelope_control_list::envelope_graph::Graph;
-use crate::gui::ui_parts::envelope_control_list::target_dropdown::TargetsList;
+use crate::gui::ui_parts::target_dropdown::TargetsList;
+use crate::params::envelope_target::ModulationSource;
 
 pub struct EnvelopeControls {}
 
@@ -52,7 +53,19 @@ impl EnvelopeControls {
                     .bottom(Pixels(5.0))
                     .top(Pixels(5.0));
 
-                TargetsList::new(cx, GuiData::params, index)
+                HStack::new(cx, move |cx| {
+                    ParamKnob::new(cx, GuiData::params, move |p| &p.envelope_params[index].attack_curve,
+                                   true, Some("A curve"), false);
+
+                    ParamKnob::new(cx, GuiData::params, move |p| &p.envelope_params[index].decay_curve,
+                                   true, Some("D curve"), false);
+
+                    ParamKnob::new(cx, GuiData::params, move |p| &p.envelope_params[index].release_curve,
+                                   true, Some("R curve"), false);
+                })
+                    .bottom(Pixels(5.0));
+
+                TargetsList::new(cx, GuiData::params, ModulationSource::Envelope(index))
                     .width(Percentage(100.0))
                     .child_left(Pixels(5.0))
                     .child_right(Pixels(5.0))
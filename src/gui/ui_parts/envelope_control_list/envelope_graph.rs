@@ -6,6 +6,9 @@ use nih_plug_vizia::vizia::vg::Paint;
 use crate::params::SynthParams;
 use crate::process::envelope::Adsr;
 
+/// The amount of lines that every segment of the envelope is drawn with
+const CURVE_STEPS: usize = 32;
+
 pub struct Graph<L>
     where L: Lens<Target=Arc<SynthParams>>
 {
@@ -57,11 +60,15 @@ impl<L> View for Graph<L>
         //         env_params.release.modulated_normalized_value(),
         //     )
         // }).get(cx);
+        let env_params = &p.envelope_params[self.index];
         let params = Adsr::new(
-            p.envelope_params[self.index].attack.modulated_normalized_value(),
-            p.envelope_params[self.index].decay.modulated_normalized_value(),
-            p.envelope_params[self.index].sustain.modulated_normalized_value(),
-            p.envelope_params[self.index].release.modulated_normalized_value(),
+            env_params.attack.modulated_normalized_value(),
+            env_params.decay.modulated_normalized_value(),
+            env_params.sustain.modulated_normalized_value(),
+            env_params.release.modulated_normalized_value(),
+            env_params.attack_curve.modulated_plain_value(),
+            env_params.decay_curve.modulated_plain_value(),
+            env_params.release_curve.modulated_plain_value(),
         );
 
         // Draw envelope curve
@@ -69,21 +76,35 @@ impl<L> View for Graph<L>
         let mut x = bounds.x;
         wave.move_to(x, baseline);
         // Attack
-        x += params.attack() * bounds.w / 3.0;
-        wave.line_to(x, bounds.y);
+        let width = params.attack() * bounds.w / 3.0;
+        add_segment(&mut wave, x, width, baseline, bounds.h, |progress| params.attack_gain(0.0, progress));
+        x += width;
         // Extra pixel to remove spike at the top of graph
         x += 1.0;
         wave.line_to(x, bounds.y);
         // Decay & sustain
-        x += params.decay() * bounds.w / 3.0;
-        wave.line_to(x, baseline - params.sustain() * bounds.h);
+        let width = params.decay() * bounds.w / 3.0;
+        add_segment(&mut wave, x, width, baseline, bounds.h, |progress| params.decay_gain(progress));
+        x += width;
         // Release
-        x += params.release() * bounds.w / 3.0;
-        wave.line_to(x, baseline);
+        let width = params.release() * bounds.w / 3.0;
+        add_segment(&mut wave, x, width, baseline, bounds.h,
+                    |progress| params.release_gain(params.sustain(), progress));
 
         // Draw
         let mut paint = Paint::color(Color::black().into());
         paint.set_line_width(1.0);
         canvas.stroke_path(&mut wave, &paint);
     }
-}
\ No newline at end of file
+}
+
+/// Add a segment of `width` pixels that starts at `x` to `path`, `gain` gives the level at a
+/// progress from 0 to 1
+fn add_segment(path: &mut vg::Path, x: f32, width: f32, baseline: f32, height: f32,
+               gain: impl Fn(f32) -> f32,
+) {
+    for step in 1..=CURVE_STEPS {
+        let progress = step as f32 / CURVE_STEPS as f32;
+        path.line_to(x + progress * width, baseline - gain(progress) * height);
+    }
+}
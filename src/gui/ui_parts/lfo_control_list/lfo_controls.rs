@@ -0,0 +1,88 @@
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::{ParamButton, ParamButtonExt};
+use crate::gui::components::knob::ParamKnob;
+use crate::gui::components::param_button_wrapper::ParamButtonWrapper;
+use crate::gui::components::selector::{ButtonLabel, get_enum_name, Selector};
+use crate::gui::events::ControlEvent;
+use crate::gui::GuiData;
+use crate::gui::ui_parts::target_dropdown::TargetsList;
+use crate::params::envelope_target::ModulationSource;
+
+pub struct LfoControls {}
+
+impl View for LfoControls {}
+
+impl LfoControls {
+    pub fn new(cx: &mut Context, index: usize) -> Handle<Self> {
+        Self {}.build(cx, move |cx| {
+            let synced = GuiData::params
+                .map(move |p| p.lfo_params[index].sync.value());
+
+            VStack::new(cx, move |cx| {
+                HStack::new(cx, move |cx| {
+                    Selector::new(cx, GuiData::params, move |p| &p.lfo_params[index].wave_kind,
+                                  |v| ButtonLabel::Text(get_enum_name(v)),
+                    );
+
+                    ParamButtonWrapper::new(
+                        cx,
+                        |cx| {
+                            ParamButton::new(cx, GuiData::params, move |p| &p.lfo_params[index].enabled)
+                                .with_label("X");
+                        },
+                        |cx| cx.emit(ControlEvent::RemoveLfo),
+                    ).width(Pixels(0.0))
+                        .top(Pixels(0.0));
+                }).child_top(Stretch(1.0))
+                    .child_bottom(Stretch(1.0))
+                    .col_between(Stretch(1.0))
+                    .width(Percentage(100.0))
+                    .top(Pixels(5.0));
+
+                HStack::new(cx, move |cx| {
+                    Binding::new(cx, synced, move |cx, synced| {
+                        if synced.get(cx) {
+                            ParamKnob::new(cx, GuiData::params, move |p| &p.lfo_params[index].division,
+                                           false, Some("Division"), false);
+                        } else {
+                            ParamKnob::new(cx, GuiData::params, move |p| &p.lfo_params[index].rate,
+                                           false, Some("Rate"), false);
+                        }
+                    });
+
+                    ParamKnob::new(cx, GuiData::params, move |p| &p.lfo_params[index].depth,
+                                   false, Some("Depth"), false);
+
+                    ParamKnob::new(cx, GuiData::params, move |p| &p.lfo_params[index].phase,
+                                   false, Some("Phase"), false);
+                })
+                    .child_space(Pixels(1.0))
+                    .col_between(Pixels(5.0));
+
+                HStack::new(cx, move |cx| {
+                    ParamButton::new(cx, GuiData::params, move |p| &p.lfo_params[index].sync)
+                        .with_label("Sync");
+
+                    Selector::new(cx, GuiData::params, move |p| &p.lfo_params[index].retrigger,
+                                  |v| ButtonLabel::Text(get_enum_name(v)),
+                    );
+                })
+                    .child_top(Stretch(1.0))
+                    .child_bottom(Stretch(1.0))
+                    .col_between(Pixels(5.0));
+
+                TargetsList::new(cx, GuiData::params, ModulationSource::Lfo(index))
+                    .width(Percentage(100.0))
+                    .child_left(Pixels(5.0))
+                    .child_right(Pixels(5.0))
+                    .row_between(Pixels(5.0))
+                    .bottom(Pixels(10.0));
+            }).row_between(Pixels(5.0))
+                .child_left(Stretch(1.0))
+                .child_right(Stretch(1.0))
+                .height(Pixels(0.0))
+                .border_color(Color::black())
+                .border_width(Pixels(1.0));
+        })
+    }
+}
@@ -0,0 +1,39 @@
+use nih_plug_vizia::vizia::prelude::*;
+use crate::CC_SOURCE_AMOUNT;
+use crate::gui::ui_parts::midi_source_list::cc_controls::CcControls;
+use crate::gui::ui_parts::midi_source_list::expression_controls::ExpressionControls;
+use crate::gui::ui_parts::midi_source_list::mpe_controls::MpeControls;
+use crate::params::envelope_target::ModulationSource;
+
+mod cc_controls;
+mod expression_controls;
+mod mpe_controls;
+
+pub struct MidiSourceList {}
+
+impl MidiSourceList {
+    pub fn new(cx: &mut Context) -> Handle<Self> {
+        Self {}.build(cx, |cx| {
+            ScrollView::new(cx, 0.0, 0.0, false, true, |cx| {
+                VStack::new(cx, |cx| {
+                    Label::new(cx, "MIDI");
+
+                    for i in 0..CC_SOURCE_AMOUNT {
+                        CcControls::new(cx, i).width(Percentage(100.0));
+                    }
+
+                    ExpressionControls::new(cx, "Poly pressure", ModulationSource::PolyPressure)
+                        .width(Percentage(100.0));
+                    ExpressionControls::new(cx, "Channel pressure", ModulationSource::ChannelPressure)
+                        .width(Percentage(100.0));
+
+                    MpeControls::new(cx).width(Percentage(100.0));
+                    ExpressionControls::new(cx, "Timbre", ModulationSource::Timbre)
+                        .width(Percentage(100.0));
+                }).row_between(Pixels(10.0)).width(Percentage(90.0));
+            }).height(Stretch(1.0)).width(Pixels(220.0));
+        })
+    }
+}
+
+impl View for MidiSourceList {}
@@ -0,0 +1,57 @@
+use nih_plug_vizia::vizia::prelude::*;
+use crate::gui::components::fake_param_button::FakeParamButton;
+use crate::gui::events::ControlEvent;
+use crate::gui::GuiData;
+use crate::gui::ui_parts::target_dropdown::TargetsList;
+use crate::params::cc_params::get_cc_name;
+use crate::params::envelope_target::ModulationSource;
+
+pub struct CcControls {}
+
+impl View for CcControls {}
+
+impl CcControls {
+    pub fn new(cx: &mut Context, index: usize) -> Handle<Self> {
+        Self {}.build(cx, move |cx| {
+            let cc = GuiData::params
+                .map(move |p| *p.cc_params[index].cc.lock().expect("Cannot lock CC number"));
+
+            VStack::new(cx, move |cx| {
+                HStack::new(cx, move |cx| {
+                    FakeParamButton::new(
+                        cx,
+                        move |cx| cx.emit(ControlEvent::ShiftCc(index, -1)),
+                        |cx| Label::new(cx, "-"),
+                    );
+
+                    Label::new(cx, cc.clone().map(|cc| format!("CC {cc}")))
+                        .width(Pixels(45.0));
+
+                    FakeParamButton::new(
+                        cx,
+                        move |cx| cx.emit(ControlEvent::ShiftCc(index, 1)),
+                        |cx| Label::new(cx, "+"),
+                    );
+
+                    Label::new(cx, cc.map(|cc| get_cc_name(*cc)));
+                }).child_top(Stretch(1.0))
+                    .child_bottom(Stretch(1.0))
+                    .col_between(Pixels(5.0))
+                    .width(Percentage(100.0))
+                    .top(Pixels(5.0));
+
+                TargetsList::new(cx, GuiData::params, ModulationSource::Cc(index))
+                    .width(Percentage(100.0))
+                    .child_left(Pixels(5.0))
+                    .child_right(Pixels(5.0))
+                    .row_between(Pixels(5.0))
+                    .bottom(Pixels(10.0));
+            }).row_between(Pixels(5.0))
+                .child_left(Stretch(1.0))
+                .child_right(Stretch(1.0))
+                .height(Pixels(0.0))
+                .border_color(Color::black())
+                .border_width(Pixels(1.0));
+        })
+    }
+}
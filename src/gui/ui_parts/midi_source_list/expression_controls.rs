@@ -0,0 +1,31 @@
+use nih_plug_vizia::vizia::prelude::*;
+use crate::gui::GuiData;
+use crate::gui::ui_parts::target_dropdown::TargetsList;
+use crate::params::envelope_target::ModulationSource;
+
+pub struct ExpressionControls {}
+
+impl View for ExpressionControls {}
+
+impl ExpressionControls {
+    pub fn new(cx: &mut Context, name: &'static str, source: ModulationSource) -> Handle<Self> {
+        Self {}.build(cx, move |cx| {
+            VStack::new(cx, move |cx| {
+                Label::new(cx, name)
+                    .top(Pixels(5.0));
+
+                TargetsList::new(cx, GuiData::params, source)
+                    .width(Percentage(100.0))
+                    .child_left(Pixels(5.0))
+                    .child_right(Pixels(5.0))
+                    .row_between(Pixels(5.0))
+                    .bottom(Pixels(10.0));
+            }).row_between(Pixels(5.0))
+                .child_left(Stretch(1.0))
+                .child_right(Stretch(1.0))
+                .height(Pixels(0.0))
+                .border_color(Color::black())
+                .border_width(Pixels(1.0));
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::{ParamButton, ParamButtonExt};
+use crate::gui::components::knob::ParamKnob;
+use crate::gui::components::selector::{ButtonLabel, get_enum_name, Selector};
+use crate::gui::GuiData;
+
+pub struct MpeControls {}
+
+impl View for MpeControls {}
+
+impl MpeControls {
+    pub fn new(cx: &mut Context) -> Handle<Self> {
+        Self {}.build(cx, |cx| {
+            VStack::new(cx, |cx| {
+                HStack::new(cx, |cx| {
+                    Label::new(cx, "MPE");
+
+                    ParamButton::new(cx, GuiData::params, |p| &p.mpe_params.enabled)
+                        .with_label("On");
+                }).child_top(Stretch(1.0))
+                    .child_bottom(Stretch(1.0))
+                    .col_between(Stretch(1.0))
+                    .width(Percentage(100.0))
+                    .top(Pixels(5.0));
+
+                Selector::new(cx, GuiData::params, |p| &p.mpe_params.zone,
+                              |v| ButtonLabel::Text(get_enum_name(v)),
+                );
+
+                HStack::new(cx, |cx| {
+                    ParamKnob::new(cx, GuiData::params, |p| &p.mpe_params.member_channels,
+                                   false, Some("Channels"), false);
+
+                    ParamKnob::new(cx, GuiData::params, |p| &p.mpe_params.bend_range,
+                                   false, Some("Bend"), false);
+                })
+                    .child_space(Pixels(1.0))
+                    .col_between(Pixels(5.0))
+                    .bottom(Pixels(5.0));
+            }).row_between(Pixels(5.0))
+                .child_left(Stretch(1.0))
+                .child_right(Stretch(1.0))
+                .height(Auto)
+                .border_color(Color::black())
+                .border_width(Pixels(1.0));
+        })
+    }
+}
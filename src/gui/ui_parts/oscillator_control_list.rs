@@ -1,11 +1,17 @@
+use enum_iterator::all;
 use nih_plug_vizia::vizia::prelude::*;
 use crate::gui::components::fake_param_button::FakeParamButton;
+use crate::gui::components::grid::{Grid, GridVerticalModifiers};
+use crate::gui::components::selector::get_enum_name;
 use crate::gui::events::ControlEvent;
 use crate::gui::GuiData;
 use crate::gui::ui_parts::oscillator_control_list::oscillator_controls::OscillatorControls;
+use crate::gui::ui_parts::sub_controls::SubControls;
+use crate::params::fm_params::FmAlgorithm;
 use crate::utils::get_oscillator_array;
 
 pub mod oscillator_controls;
+mod harmonics_editor;
 
 pub struct OscillatorControlList {}
 
@@ -16,6 +22,35 @@ impl OscillatorControlList {
         Self {}.build(cx, |cx| {
             ScrollView::new(cx, 0.0, 0.0, false, true, move |cx| {
                 VStack::new(cx, |cx| {
+                    VStack::new(cx, |cx| {
+                        Label::new(cx, "FM algorithm");
+
+                        let mut buttons = Vec::new();
+                        for algorithm in all::<FmAlgorithm>() {
+                            buttons.push(move |cx: &mut Context| {
+                                FakeParamButton::new(
+                                    cx,
+                                    move |cx| cx.emit(ControlEvent::SetFmAlgorithm(algorithm)),
+                                    move |cx| Label::new(cx, &get_enum_name(algorithm)),
+                                ).width(Pixels(80.0))
+                                    .child_space(Stretch(1.0));
+                            });
+                        }
+                        Grid::new(2,
+                                  GridVerticalModifiers {
+                                      col_between: Pixels(5.0),
+                                      child_bottom: Pixels(1.0),
+                                      child_top: Pixels(1.0),
+                                      child_left: Stretch(1.0),
+                                      child_right: Stretch(1.0),
+                                  },
+                                  cx,
+                                  buttons,
+                        ).row_between(Pixels(5.0));
+                    })
+                        .row_between(Pixels(5.0))
+                        .height(Auto);
+
                     for i in get_oscillator_array() {
                         let enabled = GuiData::params
                             .map(move |p| p.oscillator_params[i].enabled.value());
@@ -38,6 +73,8 @@ impl OscillatorControlList {
                         }
                     });
 
+                    SubControls::new(cx).width(Percentage(100.0));
+
                 }).row_between(Pixels(10.0)).width(Percentage(90.0));
             }).height(Stretch(1.0)).width(Pixels(210.0));
         })
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::vizia::vg;
+use nih_plug_vizia::vizia::vg::Paint;
+use crate::gui::events::ControlEvent;
+use crate::params::SynthParams;
+use crate::MAX_HARMONICS;
+
+/// A bar graph of the harmonics of an additive oscillator, the bars can be drawn with the mouse
+pub struct HarmonicsEditor<L>
+    where L: Lens<Target=Arc<SynthParams>>
+{
+    params: L,
+    index: usize,
+    /// The harmonic and amplitude that were last set while dragging, so the harmonics in between
+    /// can be filled in when the mouse moves quickly
+    last_drag: Option<(usize, f32)>,
+}
+
+impl<L> HarmonicsEditor<L>
+    where L: Lens<Target=Arc<SynthParams>>
+{
+    pub fn new(cx: &mut Context, params: L, index: usize) -> Handle<Self> {
+        Self {
+            params,
+            index,
+            last_drag: None,
+        }.build(cx, |_| {})
+    }
+
+    /// The harmonic and amplitude at the mouse position
+    fn harmonic_at(cx: &EventContext, x: f32, y: f32) -> (usize, f32) {
+        let bounds = cx.bounds();
+        let harmonic = ((x - bounds.x) / bounds.w * MAX_HARMONICS as f32)
+            .clamp(0.0, (MAX_HARMONICS - 1) as f32) as usize;
+        let amplitude = ((bounds.y + bounds.h - y) / bounds.h).clamp(0.0, 1.0);
+        (harmonic, amplitude)
+    }
+
+    /// Set the harmonic at the mouse position, and the harmonics between it and the last one
+    fn drag_to(&mut self, cx: &mut EventContext, x: f32, y: f32) {
+        let (harmonic, amplitude) = Self::harmonic_at(cx, x, y);
+        let (last_harmonic, last_amplitude) = self.last_drag.unwrap_or((harmonic, amplitude));
+
+        let start = harmonic.min(last_harmonic);
+        let end = harmonic.max(last_harmonic);
+        for n in start..=end {
+            let t = if end == start {
+                1.0
+            } else {
+                (n as f32 - last_harmonic as f32) / (harmonic as f32 - last_harmonic as f32)
+            };
+            let amplitude = last_amplitude + (amplitude - last_amplitude) * t;
+            cx.emit(ControlEvent::SetHarmonic(self.index, n, amplitude));
+        }
+
+        self.last_drag = Some((harmonic, amplitude));
+        cx.needs_redraw();
+    }
+}
+
+impl<L> View for HarmonicsEditor<L>
+    where L: Lens<Target=Arc<SynthParams>>
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("harmonics-editor")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match window_event {
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                cx.capture();
+                cx.set_active(true);
+                self.last_drag = None;
+                let (x, y) = (cx.mouse.cursorx, cx.mouse.cursory);
+                self.drag_to(cx, x, y);
+
+                meta.consume();
+            }
+            WindowEvent::MouseDown(MouseButton::Right) => {
+                // Right click removes a harmonic
+                let (harmonic, _) = Self::harmonic_at(cx, cx.mouse.cursorx, cx.mouse.cursory);
+                cx.emit(ControlEvent::SetHarmonic(self.index, harmonic, 0.0));
+                cx.needs_redraw();
+
+                meta.consume();
+            }
+            WindowEvent::MouseUp(MouseButton::Left) => {
+                if self.last_drag.is_some() {
+                    self.last_drag = None;
+                    cx.release();
+                    cx.set_active(false);
+
+                    meta.consume();
+                }
+            }
+            WindowEvent::MouseMove(x, y) => {
+                if self.last_drag.is_some() {
+                    self.drag_to(cx, *x, *y);
+                }
+            }
+            _ => {}
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        let baseline = bounds.y + bounds.h;
+
+        let mut bottom_line = vg::Path::new();
+        bottom_line.move_to(bounds.x, baseline);
+        bottom_line.line_to(bounds.x + bounds.w, baseline);
+        let mut paint = Paint::color(Color::black().into());
+        paint.set_line_width(1.0);
+        paint.set_anti_alias(false);
+        canvas.stroke_path(&mut bottom_line, &paint);
+
+        let p = self.params.get(cx);
+        let harmonics = p.harmonics.lock().expect("Cannot lock harmonics");
+
+        // Leave a pixel between the bars when there is room for it
+        let bar_width = bounds.w / MAX_HARMONICS as f32;
+        let gap = if bar_width > 2.0 { 1.0 } else { 0.0 };
+        let mut bars = vg::Path::new();
+        for (n, amplitude) in harmonics[self.index].iter().take(MAX_HARMONICS).enumerate() {
+            let height = amplitude.clamp(0.0, 1.0) * bounds.h;
+            if height > 0.0 {
+                bars.rect(bounds.x + n as f32 * bar_width, baseline - height, bar_width - gap, height);
+            }
+        }
+        canvas.fill_path(&mut bars, &Paint::color(Color::black().into()));
+    }
+}
@@ -1,11 +1,19 @@
+use std::path::Path;
 use nih_plug_vizia::vizia::prelude::*;
 use nih_plug_vizia::widgets::{ParamButton, ParamButtonExt};
+use crate::gui::components::fake_param_button::FakeParamButton;
 use crate::gui::components::knob::ParamKnob;
 use crate::gui::components::param_button_wrapper::ParamButtonWrapper;
 use crate::gui::components::selector::{ButtonLabel, get_enum_name, Selector};
 use crate::gui::GuiData;
+use crate::gui::ui_parts::oscillator_control_list::harmonics_editor::HarmonicsEditor;
 use crate::gui::ui_parts::oscillator_control_list::ControlEvent;
 use crate::process::note::WaveKind;
+use crate::utils::get_oscillator_array;
+use crate::OSCILLATOR_AMOUNT;
+
+/// The labels of the knobs that set how much each oscillator modulates this one
+const FM_LABELS: [&str; OSCILLATOR_AMOUNT] = ["FM 0", "FM 1", "FM 2", "FM 3"];
 
 pub struct OscillatorControls {}
 
@@ -16,6 +24,26 @@ impl OscillatorControls {
         Self {}.build(cx, move |cx| {
             let display_pwm = GuiData::params
                 .map(move |p| p.oscillator_params[i].wave_kind.value() == WaveKind::Square);
+            let display_wavetable = GuiData::params
+                .map(move |p| p.oscillator_params[i].wave_kind.value() == WaveKind::Wavetable);
+            let display_sample = GuiData::params
+                .map(move |p| p.oscillator_params[i].wave_kind.value() == WaveKind::Sample);
+            let sample_name = GuiData::params.map(move |p| {
+                p.sample_paths.lock().expect("Cannot lock sample paths")[i].as_ref()
+                    .and_then(|path| Path::new(path).file_stem())
+                    .map_or(String::from("No sample"), |name| name.to_string_lossy().into_owned())
+            });
+            let display_additive = GuiData::params
+                .map(move |p| p.oscillator_params[i].wave_kind.value() == WaveKind::Additive);
+            let display_noise = GuiData::params
+                .map(move |p| p.oscillator_params[i].wave_kind.value() == WaveKind::Noise);
+            let display_unison = GuiData::params
+                .map(move |p| p.oscillator_params[i].unison_voices.value() > 1);
+            let wavetable_name = GuiData::params.map(move |p| {
+                p.wavetable_paths.lock().expect("Cannot lock wavetable paths")[i].as_ref()
+                    .and_then(|path| Path::new(path).file_stem())
+                    .map_or(String::from("No table"), |name| name.to_string_lossy().into_owned())
+            });
 
             VStack::new(cx, move |cx| {
                 VStack::new(cx, move |cx| {
@@ -25,6 +53,9 @@ impl OscillatorControls {
                                       |v| ButtonLabel::Text(get_enum_name(v)),
                         );
 
+                        ParamButton::new(cx, GuiData::params, move |p| &p.oscillator_params[i].output)
+                            .with_label("Out");
+
                         ParamButtonWrapper::new(
                             cx,
                             |cx| {
@@ -55,6 +86,12 @@ impl OscillatorControls {
                         .col_between(Pixels(5.0));
 
                     HStack::new(cx, move |cx| {
+                        ParamButton::new(cx, GuiData::params, move |p| &p.oscillator_params[i].antialias)
+                            .with_label("AA");
+
+                        ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].pan,
+                                       true, Some("Pan"), false);
+
                         Binding::new(cx, display_pwm, move |cx, display| {
                             if display.get(cx) {
                                 ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].pulse_width,
@@ -65,6 +102,169 @@ impl OscillatorControls {
                         .class("osc-buttons")
                         .child_space(Pixels(1.0))
                         .col_between(Pixels(5.0));
+
+                    Binding::new(cx, display_sample, move |cx, display| {
+                        if display.get(cx) {
+                            let sample_name = sample_name.clone();
+                            HStack::new(cx, move |cx| {
+                                VStack::new(cx, move |cx| {
+                                    FakeParamButton::new(
+                                        cx,
+                                        move |cx| cx.emit(ControlEvent::LoadSample(i)),
+                                        |cx| Label::new(cx, "Load"),
+                                    ).child_space(Stretch(1.0));
+
+                                    Label::new(cx, sample_name)
+                                        .width(Pixels(80.0));
+                                })
+                                    .child_top(Stretch(1.0))
+                                    .child_bottom(Stretch(1.0))
+                                    .row_between(Pixels(5.0));
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].sample.root_key,
+                                               false, Some("Root"), false);
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].sample.start,
+                                               false, Some("Start"), false);
+                            })
+                                .class("osc-buttons")
+                                .child_space(Pixels(1.0))
+                                .col_between(Pixels(5.0));
+
+                            Selector::new(cx, GuiData::params, move |p| &p.oscillator_params[i].sample.mode,
+                                          |v| ButtonLabel::Text(get_enum_name(v)),
+                            );
+
+                            HStack::new(cx, move |cx| {
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].sample.loop_start,
+                                               false, Some("Loop start"), false);
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].sample.loop_end,
+                                               false, Some("Loop end"), false);
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].sample.crossfade,
+                                               false, Some("Crossfade"), false);
+                            })
+                                .class("osc-buttons")
+                                .child_space(Pixels(1.0))
+                                .col_between(Pixels(5.0));
+                        }
+                    });
+
+                    Binding::new(cx, display_additive, move |cx, display| {
+                        if display.get(cx) {
+                            HarmonicsEditor::new(cx, GuiData::params, i)
+                                .width(Percentage(100.0))
+                                .height(Pixels(60.0));
+
+                            HStack::new(cx, move |cx| {
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].additive_balance,
+                                               true, Some("Odd/Even"), false);
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].additive_tilt,
+                                               true, Some("Tilt"), false);
+                            })
+                                .class("osc-buttons")
+                                .child_space(Pixels(1.0))
+                                .col_between(Pixels(5.0));
+                        }
+                    });
+
+                    Binding::new(cx, display_noise, move |cx, display| {
+                        if display.get(cx) {
+                            Selector::new(cx, GuiData::params, move |p| &p.oscillator_params[i].noise_color,
+                                          |v| ButtonLabel::Text(get_enum_name(v)),
+                            );
+                        }
+                    });
+
+                    HStack::new(cx, move |cx| {
+                        ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].unison_voices,
+                                       false, Some("Unison"), false);
+
+                        Binding::new(cx, display_unison, move |cx, display| {
+                            if display.get(cx) {
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].unison_detune,
+                                               false, Some("Spread"), false);
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].unison_blend,
+                                               false, Some("Blend"), false);
+                            }
+                        });
+                    })
+                        .class("osc-buttons")
+                        .child_space(Pixels(1.0))
+                        .col_between(Pixels(5.0));
+
+                    Binding::new(cx, display_unison, move |cx, display| {
+                        if display.get(cx) {
+                            HStack::new(cx, move |cx| {
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].unison_width,
+                                               false, Some("Width"), false);
+
+                                VStack::new(cx, move |cx| {
+                                    Selector::new(cx, GuiData::params, move |p| &p.oscillator_params[i].unison_curve,
+                                                  |v| ButtonLabel::Text(get_enum_name(v)),
+                                    );
+
+                                    ParamButton::new(cx, GuiData::params, move |p| &p.oscillator_params[i].unison_random_phase)
+                                        .with_label("Random phase");
+                                })
+                                    .child_top(Stretch(1.0))
+                                    .child_bottom(Stretch(1.0))
+                                    .row_between(Pixels(5.0));
+                            })
+                                .class("osc-buttons")
+                                .child_space(Pixels(1.0))
+                                .col_between(Pixels(5.0));
+                        }
+                    });
+
+                    HStack::new(cx, move |cx| {
+                        for (modulator, label) in get_oscillator_array().into_iter().zip(FM_LABELS) {
+                            ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].fm[modulator].index,
+                                           false, Some(label), false);
+                        }
+                    })
+                        .class("osc-buttons")
+                        .class("fm-knobs")
+                        .child_space(Pixels(1.0))
+                        .col_between(Pixels(5.0));
+
+                    // The first oscillator has no previous oscillator to combine with
+                    if i > 0 {
+                        Label::new(cx, "Combine");
+                        Selector::new(cx, GuiData::params, move |p| &p.oscillator_params[i].combine,
+                                      |v| ButtonLabel::Text(get_enum_name(v)),
+                        );
+                    }
+
+                    Binding::new(cx, display_wavetable, move |cx, display| {
+                        if display.get(cx) {
+                            let wavetable_name = wavetable_name.clone();
+                            HStack::new(cx, move |cx| {
+                                VStack::new(cx, move |cx| {
+                                    FakeParamButton::new(
+                                        cx,
+                                        move |cx| cx.emit(ControlEvent::LoadWavetable(i)),
+                                        |cx| Label::new(cx, "Load"),
+                                    ).child_space(Stretch(1.0));
+
+                                    Label::new(cx, wavetable_name)
+                                        .width(Pixels(80.0));
+                                })
+                                    .child_top(Stretch(1.0))
+                                    .child_bottom(Stretch(1.0))
+                                    .row_between(Pixels(5.0));
+
+                                ParamKnob::new(cx, GuiData::params, move |p| &p.oscillator_params[i].wavetable_position,
+                                               false, Some("Position"), false);
+                            })
+                                .class("osc-buttons")
+                                .child_space(Pixels(1.0))
+                                .col_between(Pixels(5.0));
+                        }
+                    });
                 })
                     .row_between(Pixels(0.0))
                     .child_left(Stretch(1.0))
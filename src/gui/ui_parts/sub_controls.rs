@@ -0,0 +1,50 @@
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::{ParamButton, ParamButtonExt};
+use crate::gui::components::knob::ParamKnob;
+use crate::gui::components::selector::{ButtonLabel, get_enum_name, Selector};
+use crate::gui::GuiData;
+
+pub struct SubControls {}
+
+impl View for SubControls {}
+
+impl SubControls {
+    pub fn new(cx: &mut Context) -> Handle<Self> {
+        Self {}.build(cx, |cx| {
+            VStack::new(cx, |cx| {
+                HStack::new(cx, |cx| {
+                    Label::new(cx, "Sub");
+
+                    ParamButton::new(cx, GuiData::params, |p| &p.sub_params.enabled)
+                        .with_label("On");
+                }).child_top(Stretch(1.0))
+                    .child_bottom(Stretch(1.0))
+                    .col_between(Stretch(1.0))
+                    .width(Percentage(100.0))
+                    .bottom(Pixels(5.0));
+
+                Selector::new(cx, GuiData::params, |p| &p.sub_params.shape,
+                              |v| ButtonLabel::Text(get_enum_name(v)),
+                );
+
+                HStack::new(cx, |cx| {
+                    ParamKnob::new(cx, GuiData::params, |p| &p.sub_params.octaves,
+                                   false, Some("Octaves"), false);
+
+                    ParamKnob::new(cx, GuiData::params, |p| &p.sub_params.volume,
+                                   false, Some("Volume"), false);
+                })
+                    .class("osc-buttons")
+                    .child_space(Pixels(1.0))
+                    .col_between(Pixels(5.0));
+            })
+                .row_between(Pixels(5.0))
+                .child_left(Stretch(1.0))
+                .child_right(Stretch(1.0))
+                .child_space(Pixels(5.0))
+                .height(Auto)
+                .border_color(Color::black())
+                .border_width(Pixels(1.0));
+        })
+    }
+}
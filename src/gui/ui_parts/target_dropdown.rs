@@ -4,36 +4,36 @@ use nih_plug_vizia::vizia::prelude::*;
 use crate::gui::components::fake_param_button::FakeParamButton;
 use crate::gui::components::fake_param_slider::{FakeParamSlider, SliderHandle};
 use crate::gui::events::ControlEvent;
-use crate::params::envelope_target::{EnvelopeTargets, get_possible_targets, Target};
+use crate::params::envelope_target::{EnvelopeTargets, get_possible_targets, ModulationSource, Target};
 use crate::params::SynthParams;
 
 pub struct TargetsList<L>
     where L: Lens<Target=Arc<SynthParams>> + Copy,
 {
     params: L,
-    envelope_index: usize,
+    source: ModulationSource,
 }
 
 impl<L> TargetsList<L>
     where L: Lens<Target=Arc<SynthParams>> + Copy,
 {
-    pub fn new(cx: &mut Context, params: L, envelope_index: usize) -> Handle<Self>
+    pub fn new(cx: &mut Context, params: L, source: ModulationSource) -> Handle<Self>
     {
         Self {
             params,
-            envelope_index,
+            source,
         }.build(cx, |cx| {
             let data = params.get(cx);
             EnvelopeTargets::from(
-                data.envelope_params[envelope_index].targets
-                    .lock().expect("Cannot lock envelope targets")
+                source.targets(&data)
+                    .lock().expect("Cannot lock modulation targets")
             ).build(cx);
 
             HStack::new(cx, |cx| {
                 Label::new(cx, "Targets:");
                 FakeParamButton::new(cx,
                                      move |cx| {
-                                         cx.emit(ControlEvent::AddEnvelopeTarget);
+                                         cx.emit(ControlEvent::AddTarget);
                                      },
                                      |cx| Label::new(cx, "+"))
                     .left(Stretch(1.0))
@@ -45,7 +45,7 @@ impl<L> TargetsList<L>
 
             List::new(cx, EnvelopeTargets::targets, move |cx, i, _| {
                 // TODO use lens instead of index
-                TargetSelector::new(cx, params, envelope_index, i);
+                TargetSelector::new(cx, params, source, i);
             })
                 .width(Stretch(1.0))
                 .row_between(Pixels(1.0));
@@ -60,10 +60,10 @@ impl<L> View for TargetsList<L>
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
         event.map(|control_event: &ControlEvent, _meta|
             match control_event {
-                ControlEvent::AddEnvelopeTarget | ControlEvent::RemoveEnvelopeTarget(_) => {
+                ControlEvent::AddTarget | ControlEvent::RemoveTarget(_) => {
                     let data = self.params.get(cx);
-                    let mut targets = data.envelope_params[self.envelope_index].targets
-                        .lock().expect("Cannot lock envelope targets");
+                    let mut targets = self.source.targets(&data)
+                        .lock().expect("Cannot lock modulation targets");
                     targets.targets = EnvelopeTargets::targets.get(cx);
                 }
                 _ => {}
@@ -81,12 +81,12 @@ impl View for TargetSelector {
 }
 
 impl TargetSelector {
-    pub fn new<L>(cx: &mut Context, params: L, envelope_index: usize, target_index: usize) -> Handle<Self>
+    pub fn new<L>(cx: &mut Context, params: L, source: ModulationSource, target_index: usize) -> Handle<Self>
         where L: Lens<Target=Arc<SynthParams>> + Copy,
     {
         let current_target = params.map(move |p| {
-            p.envelope_params[envelope_index].targets
-                .lock().expect("Cannot lock envelope targets")
+            source.targets(p)
+                .lock().expect("Cannot lock modulation targets")
                 .targets[target_index]
         });
 
@@ -109,8 +109,8 @@ impl TargetSelector {
                                     .font_family(
                                         if target == active_target { vec![FamilyOwned::Name(String::from(assets::NOTO_SANS_BOLD))] } else { vec![FamilyOwned::Name(String::from(assets::NOTO_SANS_LIGHT))] })
                                     .on_press(move |cx| {
-                                        cx.emit(ControlEvent::SetEnvelopeTarget(
-                                            envelope_index, target_index, target,
+                                        cx.emit(ControlEvent::SetTarget(
+                                            source, target_index, target,
                                         ));
                                         cx.emit(PopupEvent::Close);
                                     })
@@ -128,15 +128,15 @@ impl TargetSelector {
 
 
                 // TODO get default value from somewhere?
-                FakeParamSlider::new(cx, TargetData::depth, 1.0)
+                FakeParamSlider::new(cx, TargetData::depth, 1.0, -1.0, 1.0)
                     .on_changing(move |cx, value| {
-                        cx.emit(ControlEvent::SetEnvelopeTargetDepth(envelope_index, target_index, value))
+                        cx.emit(ControlEvent::SetTargetDepth(source, target_index, value))
                     })
                     .width(Stretch(1.0));
 
                 FakeParamButton::new(cx,
                                      move |cx| {
-                                         cx.emit(ControlEvent::RemoveEnvelopeTarget(target_index))
+                                         cx.emit(ControlEvent::RemoveTarget(target_index))
                                      },
                                      |cx| {
                                          Label::new(cx, "-")
@@ -170,10 +170,10 @@ impl Model for TargetData {
     fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
         event.map(|control_event: &ControlEvent, _meta|
             match control_event {
-                ControlEvent::SetEnvelopeTarget(_, _, target) => {
+                ControlEvent::SetTarget(_, _, target) => {
                     self.target = *target;
                 }
-                ControlEvent::SetEnvelopeTargetDepth(_, _, depth) => {
+                ControlEvent::SetTargetDepth(_, _, depth) => {
                     self.depth = *depth;
                 }
                 _ => {}
@@ -2,6 +2,7 @@ use std::time::Duration;
 use nih_plug::util;
 use nih_plug_vizia::vizia::prelude::*;
 use nih_plug_vizia::widgets::{ParamSlider, PeakMeter};
+use crate::gui::components::knob::ParamKnob;
 use crate::gui::GuiData;
 use crate::gui::ui_parts::visualiser::scope::Scope;
 
@@ -24,13 +25,25 @@ impl Visualiser {
                     Label::new(cx, "Volume");
                     ParamSlider::new(cx, GuiData::params, |params| &params.volume);
 
-                    PeakMeter::new(
-                        cx,
-                        visual_data_lens.clone().map(
-                            |d| util::gain_to_db_fast(d.peak_meter)
-                        ),
-                        Some(Duration::from_millis(600)),
-                    );
+                    HStack::new(cx, |cx| {
+                        PeakMeter::new(
+                            cx,
+                            visual_data_lens.clone().map(
+                                |d| util::gain_to_db_fast(d.peak_meter)
+                            ),
+                            Some(Duration::from_millis(600)),
+                        );
+
+                        Label::new(cx, visual_data_lens.clone().map(
+                            |d| format!("{} voices", d.voice_count)
+                        ))
+                            .width(Pixels(60.0))
+                            .font_size(12.0);
+                    })
+                        .col_between(Pixels(5.0))
+                        .child_top(Stretch(1.0))
+                        .child_bottom(Stretch(1.0))
+                        .height(Auto);
                 })
                     .row_between(Pixels(0.0))
                     .child_left(Stretch(1.0))
@@ -39,9 +52,30 @@ impl Visualiser {
                 Scope::new(cx, visual_data_lens.clone())
                     .height(Pixels(200.0));
 
-                VStack::new(cx, |_cx| {
+                HStack::new(cx, |cx| {
+                    Label::new(cx, "Sustain")
+                        .class("pedal")
+                        .checked(visual_data_lens.clone().map(|d| d.pedals.sustain));
+                    Label::new(cx, "Sostenuto")
+                        .class("pedal")
+                        .checked(visual_data_lens.clone().map(|d| d.pedals.sostenuto));
+                })
+                    .col_between(Pixels(10.0))
+                    .child_left(Stretch(1.0))
+                    .child_right(Stretch(1.0))
+                    .height(Auto);
+
+                HStack::new(cx, |cx| {
+                    ParamKnob::new(cx, GuiData::params, |p| &p.bend_up,
+                                   false, Some("Bend up"), false);
 
-                });
+                    ParamKnob::new(cx, GuiData::params, |p| &p.bend_down,
+                                   false, Some("Bend down"), false);
+                })
+                    .col_between(Pixels(5.0))
+                    .child_left(Stretch(1.0))
+                    .child_right(Stretch(1.0))
+                    .height(Auto);
             })
                 .row_between(Pixels(0.0));
         })
@@ -0,0 +1,58 @@
+use nih_plug_vizia::vizia::prelude::*;
+use crate::gui::components::knob::ParamKnob;
+use crate::gui::components::selector::{ButtonLabel, get_enum_name, Selector};
+use crate::gui::GuiData;
+
+pub struct VoiceControls {}
+
+impl View for VoiceControls {}
+
+impl VoiceControls {
+    pub fn new(cx: &mut Context) -> Handle<Self> {
+        Self {}.build(cx, |cx| {
+            VStack::new(cx, |cx| {
+                Label::new(cx, "Voices");
+
+                Selector::new(cx, GuiData::params, |p| &p.voice_mode,
+                              |v| ButtonLabel::Text(get_enum_name(v)),
+                );
+
+                HStack::new(cx, |cx| {
+                    ParamKnob::new(cx, GuiData::params, |p| &p.polyphony,
+                                   false, Some("Polyphony"), false);
+
+                    ParamKnob::new(cx, GuiData::params, |p| &p.glide_time,
+                                   false, Some("Glide"), false);
+
+                    ParamKnob::new(cx, GuiData::params, |p| &p.stereo_spread,
+                                   false, Some("Spread"), false);
+                })
+                    .class("osc-buttons")
+                    .child_space(Pixels(1.0))
+                    .col_between(Pixels(5.0));
+
+                Label::new(cx, "Stealing");
+                Selector::new(cx, GuiData::params, |p| &p.voice_stealing,
+                              |v| ButtonLabel::Text(get_enum_name(v)),
+                );
+
+                Label::new(cx, "Priority");
+                Selector::new(cx, GuiData::params, |p| &p.note_priority,
+                              |v| ButtonLabel::Text(get_enum_name(v)),
+                );
+
+                Label::new(cx, "Glide mode");
+                Selector::new(cx, GuiData::params, |p| &p.glide_mode,
+                              |v| ButtonLabel::Text(get_enum_name(v)),
+                );
+            })
+                .row_between(Pixels(5.0))
+                .child_left(Stretch(1.0))
+                .child_right(Stretch(1.0))
+                .child_space(Pixels(5.0))
+                .height(Auto)
+                .border_color(Color::black())
+                .border_width(Pixels(1.0));
+        })
+    }
+}
@@ -2,8 +2,13 @@ use std::sync::{Arc, Mutex};
 use nih_plug::prelude::*;
 use triple_buffer::TripleBuffer;
 use crate::params::SynthParams;
+use crate::process::additive::Harmonics;
+use crate::process::modulation::ModulationRouting;
 use crate::process::notes::NoteStorage;
 use crate::process::visual_data::{SynthData, VisualData};
+use crate::process::sample::{Sample, Samples};
+use crate::process::wavetable::{Wavetable, Wavetables};
+use crate::utils::swap_slot::SwapSlot;
 
 mod gui;
 mod params;
@@ -14,15 +19,46 @@ mod utils;
 pub const OSCILLATOR_AMOUNT: usize = 4;
 /// The maximum amount of envelopes the synth can use
 pub const ENVELOPE_AMOUNT: usize = 4;
+/// The maximum amount of LFOs the synth can use
+pub const LFO_AMOUNT: usize = 4;
+/// The maximum amount of voices that can play at the same time
+pub const MAX_POLYPHONY: usize = 64;
+/// The amount of MIDI CCs that can be used as modulation source
+pub const CC_SOURCE_AMOUNT: usize = 4;
+/// The amount of MIDI channels
+pub const MIDI_CHANNELS: usize = 16;
+/// The maximum amount of unison voices of an oscillator
+pub const MAX_UNISON: usize = 16;
+/// The maximum amount of samples that are rendered at once, the modulation is calculated once per
+/// block
+pub const MAX_BLOCK_SIZE: usize = 32;
+/// The maximum amount of harmonics of an additive oscillator
+pub const MAX_HARMONICS: usize = 64;
 /// The time it takes for the peak meter to decay by 12 dB after switching to complete silence.
 const PEAK_METER_DECAY_MS: f64 = 150.0;
 
+/// Work that is too slow for the audio thread, which is done on a background thread
+pub enum Task {
+    /// Load the file at the path as the wavetable of an oscillator
+    LoadWavetable(usize, String),
+    /// Load the file at the path as the sample of an oscillator
+    LoadSample(usize, String),
+    /// Drop the values that the audio thread replaced, which it cannot deallocate itself
+    CollectRetired,
+}
+
 pub struct Synth {
     params: Arc<SynthParams>,
     sample_rate: f32,
     notes: NoteStorage,
     data: SynthData,
     visual_data: Arc<Mutex<triple_buffer::Output<VisualData>>>,
+    wavetables: Arc<Wavetables>,
+    samples: Arc<Samples>,
+    /// The modulation targets, which are changed by the GUI
+    routing: Arc<SwapSlot<ModulationRouting>>,
+    /// The harmonics of the additive oscillators, which are drawn in the GUI
+    harmonics: Arc<SwapSlot<Harmonics>>,
     // param_cache: ParamCache,
 }
 
@@ -37,6 +73,10 @@ impl Default for Synth {
             notes: NoteStorage::new(),
             data: SynthData::new(synth_data_input),
             visual_data: Arc::new(Mutex::new(synth_data_output)),
+            wavetables: Arc::new(Wavetables::new()),
+            samples: Arc::new(Samples::new()),
+            routing: Arc::new(SwapSlot::new()),
+            harmonics: Arc::new(SwapSlot::new()),
             // param_cache: ParamCache::default(),
         }
     }
@@ -63,27 +103,62 @@ impl Plugin for Synth {
         },
     ];
 
-    const MIDI_INPUT: MidiConfig = MidiConfig::Basic;
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
 
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = Task;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
     }
 
-    fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let samples = self.samples.clone();
+        let wavetables = self.wavetables.clone();
+        let routing = self.routing.clone();
+        let harmonics = self.harmonics.clone();
+        let params = self.params.clone();
+
+        Box::new(move |task| match task {
+            Task::LoadWavetable(oscillator, path) => match Wavetable::from_wav(&path) {
+                Ok(table) => {
+                    wavetables.set(oscillator, Some(table));
+                    params.wavetable_paths.lock().expect("Cannot lock wavetable paths")[oscillator] = Some(path);
+                }
+                Err(e) => nih_error!("Failed to load wavetable {path}: {e}"),
+            },
+            Task::LoadSample(oscillator, path) => match Sample::from_file(&path) {
+                Ok(sample) => {
+                    samples.set(oscillator, Some(sample));
+                    params.sample_paths.lock().expect("Cannot lock sample paths")[oscillator] = Some(path);
+                }
+                Err(e) => nih_error!("Failed to load sample {path}: {e}"),
+            },
+            Task::CollectRetired => {
+                samples.collect_retired();
+                wavetables.collect_retired();
+                routing.collect_retired();
+                harmonics.collect_retired();
+            }
+        })
+    }
+
+    fn editor(&mut self, async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         gui::create(
             self.params.clone(),
             self.params.editor_state.clone(),
             self.visual_data.clone(),
+            self.routing.clone(),
+            self.harmonics.clone(),
+            async_executor,
         )
     }
 
-    fn initialize(&mut self, _audio_io_layout: &AudioIOLayout, buffer_config: &BufferConfig, _context: &mut impl InitContext<Self>) -> bool {
+    fn initialize(&mut self, _audio_io_layout: &AudioIOLayout, buffer_config: &BufferConfig, context: &mut impl InitContext<Self>) -> bool {
         self.sample_rate = buffer_config.sample_rate;
+        self.notes.set_sample_rate(buffer_config.sample_rate);
 
         // After `PEAK_METER_DECAY_MS` milliseconds of pure silence, the peak meter's value should
         // have dropped by 12 dB
@@ -91,58 +166,163 @@ impl Plugin for Synth {
             .powf((buffer_config.sample_rate as f64 * PEAK_METER_DECAY_MS / 1000.0).recip())
             as f32);
 
+        // Load the wavetables and samples that were saved in the plugin state
+        let wavetable_paths = self.params.wavetable_paths.lock().unwrap().clone();
+        for (oscillator, path) in wavetable_paths.into_iter().enumerate() {
+            match path {
+                Some(path) => context.execute(Task::LoadWavetable(oscillator, path)),
+                None => self.wavetables.set(oscillator, None),
+            }
+        }
+        let sample_paths = self.params.sample_paths.lock().unwrap().clone();
+        for (oscillator, path) in sample_paths.into_iter().enumerate() {
+            match path {
+                Some(path) => context.execute(Task::LoadSample(oscillator, path)),
+                None => self.samples.set(oscillator, None),
+            }
+        }
+        // The modulation targets can be changed by loading the plugin state as well
+        self.routing.send(ModulationRouting::from_params(&self.params));
+        self.harmonics.send(Harmonics::from_params(&self.params));
+        if self.notes.receive_updates(&self.routing, &self.harmonics, &self.wavetables, &self.samples) {
+            context.execute(Task::CollectRetired);
+        }
+
         // Load initial param data
         true
     }
 
+    fn reset(&mut self) {
+        self.notes.reset_random();
+    }
+
     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, context: &mut impl ProcessContext<Self>) -> ProcessStatus {
-        for (_sample_id, channel_samples) in buffer.iter_samples().enumerate() {
-            // Get ui parameters
-            let volume = self.params.volume.smoothed.next();
-            // Update oscillator and envelope parameters
-            self.notes.update(&self.params);
+        let tempo = context.transport().tempo.unwrap_or(120.0) as f32;
+        if self.notes.receive_updates(&self.routing, &self.harmonics, &self.wavetables, &self.samples) {
+            // Large samples and wavetables can stay in memory for a long time otherwise
+            context.execute_background(Task::CollectRetired);
+        }
 
-            // Process midi (modifies `self.notes` and `self.released_notes`)
-            let mut next_event = context.next_event();
+        self.render(buffer.as_slice(), tempo, &mut ContextEvents(context));
 
-            // Loop over midi events
+        ProcessStatus::Normal
+    }
+}
+
+/// The note events that go in and out of [`Synth::render`]
+trait NoteEvents {
+    fn next_event(&mut self) -> Option<PluginNoteEvent<Synth>>;
+    fn send_event(&mut self, event: PluginNoteEvent<Synth>);
+}
+
+/// The note events of the host, through the process context
+struct ContextEvents<'a, C: ProcessContext<Synth>>(&'a mut C);
+
+impl<C: ProcessContext<Synth>> NoteEvents for ContextEvents<'_, C> {
+    fn next_event(&mut self) -> Option<PluginNoteEvent<Synth>> {
+        self.0.next_event()
+    }
+
+    fn send_event(&mut self, event: PluginNoteEvent<Synth>) {
+        self.0.send_event(event);
+    }
+}
+
+impl Synth {
+    /// Render the `output` channels, which all have the same length, and apply the `events` at
+    /// their sample
+    fn render(&mut self, output: &mut [&mut [f32]], tempo: f32, events: &mut impl NoteEvents) {
+        let num_samples = output.first().map_or(0, |channel| channel.len());
+        // Kept across blocks, so an event that belongs to a later block is not lost
+        let mut next_event = events.next_event();
+
+        // The buffer is split into blocks which end at the next MIDI event, so the events are
+        // still applied at the right sample
+        let mut block_start = 0;
+        while block_start < num_samples {
+            // Process the midi events at the start of this block (modifies `self.notes`)
             while let Some(event) = next_event {
-                // Break if we encounter midi events for the next sample buffer
-                // Removed because it introduced bugs
-                // TODO find out whether this has performance effects
-                // if event.timing() > sample_id as u32 {
-                //     break;
-                // }
+                if event.timing() > block_start as u32 {
+                    break;
+                }
 
                 self.notes.process_midi(
                     event,
+                    &self.params,
                     self.sample_rate,
                 );
 
-                next_event = context.next_event();
+                next_event = events.next_event();
             }
 
-            // Calculate output value, by summing all waves
-            let new_sample = self.notes.get_sample_value() * util::db_to_gain_fast(volume);
+            let block_end = (block_start + MAX_BLOCK_SIZE)
+                .min(num_samples)
+                .min(next_event.map_or(num_samples, |event| event.timing() as usize));
+            let block_len = block_end - block_start;
+
+            // Update oscillator and envelope parameters
+            self.notes.update(&self.params, tempo, block_len);
+
+            // Calculate output values, by summing all voices
+            let mut left = [0.0; MAX_BLOCK_SIZE];
+            let mut right = [0.0; MAX_BLOCK_SIZE];
+            self.notes.render_block(&mut left[..block_len], &mut right[..block_len]);
+
+            let mut volume = [0.0; MAX_BLOCK_SIZE];
+            self.params.volume.smoothed.next_block(&mut volume, block_len);
+            for ((left, right), volume) in left.iter_mut().zip(&mut right).zip(volume).take(block_len) {
+                let gain = util::db_to_gain_fast(volume);
+                *left *= gain;
+                *right *= gain;
+            }
 
-            for sample in channel_samples {
-                *sample = new_sample;
+            let channel_count = output.len();
+            for (channel, samples) in output.iter_mut().enumerate() {
+                for (i, sample) in samples[block_start..block_end].iter_mut().enumerate() {
+                    *sample = match (channel_count, channel) {
+                        // Mix down to mono
+                        (1, _) => (left[i] + right[i]) / 2.0,
+                        (_, 0) => left[i],
+                        _ => right[i],
+                    };
+                }
             }
 
-            // Remove finished notes
+            // Remove finished notes, and let the host know which voices ended
             self.notes.remove_finished_notes();
+            for event in self.notes.drain_terminated_voices(block_end as u32 - 1) {
+                events.send_event(event);
+            }
 
             // Calculate volume meter
             if self.params.editor_state.is_open() {
-                self.data.set_visual_data(new_sample);
+                for (left, right) in left.iter().zip(&right).take(block_len) {
+                    self.data.set_visual_data((left + right) / 2.0,
+                                              self.notes.pedals(), self.notes.voice_count());
+                }
             }
-        }
 
-        ProcessStatus::Normal
+            block_start = block_end;
+        }
     }
 }
 
-impl Synth {}
+impl ClapPlugin for Synth {
+    const CLAP_ID: &'static str = "nl.dovatvis.synth";
+    const CLAP_DESCRIPTION: Option<&'static str> = Some("A polyphonic synthesizer");
+    const CLAP_MANUAL_URL: Option<&'static str> = Some(Self::URL);
+    const CLAP_SUPPORT_URL: Option<&'static str> = None;
+    const CLAP_FEATURES: &'static [ClapFeature] = &[
+        ClapFeature::Instrument,
+        ClapFeature::Synthesizer,
+        ClapFeature::Stereo,
+    ];
+
+    const CLAP_POLY_MODULATION_CONFIG: Option<PolyModulationConfig> = Some(PolyModulationConfig {
+        max_voice_capacity: MAX_POLYPHONY as u32,
+        supports_overlapping_voices: true,
+    });
+}
 
 impl Vst3Plugin for Synth {
     const VST3_CLASS_ID: [u8; 16] = *b"SineMoistestPlug";
@@ -153,4 +333,67 @@ impl Vst3Plugin for Synth {
     ];
 }
 
-nih_export_vst3!(Synth);
\ No newline at end of file
+nih_export_clap!(Synth);
+nih_export_vst3!(Synth);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    fn note_on(timing: u32) -> PluginNoteEvent<Synth> {
+        NoteEvent::NoteOn { timing, voice_id: None, channel: 0, note: 60, velocity: 1.0 }
+    }
+
+    /// The events of a test, the events that are sent back are ignored
+    struct TestEvents(std::vec::IntoIter<PluginNoteEvent<Synth>>);
+
+    impl NoteEvents for TestEvents {
+        fn next_event(&mut self) -> Option<PluginNoteEvent<Synth>> {
+            self.0.next()
+        }
+
+        fn send_event(&mut self, _event: PluginNoteEvent<Synth>) {}
+    }
+
+    /// Render the `events` with the default parameters, returns the left channel
+    fn render(events: &[PluginNoteEvent<Synth>], num_samples: usize) -> Vec<f32> {
+        let mut synth = Synth::default();
+        for (_, param, _) in synth.params.param_map() {
+            // SAFETY: the parameters live as long as `synth`
+            unsafe { param.update_smoother(SAMPLE_RATE, true) };
+        }
+        synth.sample_rate = SAMPLE_RATE;
+        synth.notes.set_sample_rate(SAMPLE_RATE);
+
+        let mut left = vec![0.0; num_samples];
+        let mut right = vec![0.0; num_samples];
+        let mut events = TestEvents(events.to_vec().into_iter());
+        synth.render(&mut [left.as_mut_slice(), right.as_mut_slice()], 120.0, &mut events);
+        left
+    }
+
+    /// A note that starts at `offset` is silent before it, and sounds the same as a note that
+    /// starts at the beginning of the buffer
+    fn assert_note_starts_at(offset: usize) {
+        let num_samples = offset + 2 * MAX_BLOCK_SIZE;
+        let reference = render(&[note_on(0)], num_samples);
+        let output = render(&[note_on(offset as u32)], num_samples);
+
+        assert!(output[..offset].iter().all(|sample| *sample == 0.0));
+        assert_eq!(output[offset..], reference[..num_samples - offset]);
+        // The attack starts at a gain of 0, so the first sample of the note is still silent
+        assert!(output[offset + 1..].iter().all(|sample| *sample != 0.0));
+    }
+
+    #[test]
+    fn note_starts_within_a_block() {
+        assert_note_starts_at(MAX_BLOCK_SIZE / 2 + 3);
+    }
+
+    #[test]
+    fn note_starts_after_a_block_boundary() {
+        assert_note_starts_at(2 * MAX_BLOCK_SIZE + 5);
+    }
+}
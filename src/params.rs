@@ -1,14 +1,29 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use nih_plug::prelude::*;
 use nih_plug_vizia::ViziaState;
-use crate::{ENVELOPE_AMOUNT, gui, OSCILLATOR_AMOUNT};
+use crate::{CC_SOURCE_AMOUNT, ENVELOPE_AMOUNT, gui, LFO_AMOUNT, MAX_POLYPHONY, OSCILLATOR_AMOUNT};
+use crate::params::cc_params::{CcParams, DEFAULT_CCS};
 use crate::params::envelope_params::EnvelopeParams;
+use crate::params::envelope_target::EnvelopeTargets;
+use crate::params::filter_params::FilterParams;
+use crate::params::lfo_params::LfoParams;
+use crate::params::mpe_params::MpeParams;
 use crate::params::oscillator_params::OscillatorParams;
-use crate::utils::{get_envelope_array, get_oscillator_array};
+use crate::params::sub_params::SubParams;
+use crate::process::additive::default_harmonics;
+use crate::process::voice::{GlideMode, NotePriority, VoiceMode, VoiceStealing};
+use crate::utils::{get_envelope_array, get_lfo_array, get_oscillator_array};
 
+pub mod cc_params;
 mod envelope_params;
+pub mod fm_params;
 pub mod envelope_target;
-mod oscillator_params;
+mod filter_params;
+mod lfo_params;
+mod mpe_params;
+pub mod oscillator_params;
+mod sample_params;
+mod sub_params;
 
 pub trait Enable {
     fn enabled(&self) -> &BoolParam;
@@ -21,14 +36,73 @@ pub struct SynthParams {
     #[persist = "editor-state"]
     pub editor_state: Arc<ViziaState>,
 
+    /// The paths of the wavetables loaded for each oscillator, so they can be reloaded together
+    /// with the rest of the state.
+    #[persist = "wavetables"]
+    pub wavetable_paths: Arc<Mutex<[Option<String>; OSCILLATOR_AMOUNT]>>,
+
+    /// The paths of the samples loaded for each oscillator, so they can be reloaded together with
+    /// the rest of the state.
+    #[persist = "samples"]
+    pub sample_paths: Arc<Mutex<[Option<String>; OSCILLATOR_AMOUNT]>>,
+
+    /// The amplitude of every harmonic of the additive oscillators, as drawn in the GUI
+    #[persist = "harmonics"]
+    pub harmonics: Arc<Mutex<[Vec<f32>; OSCILLATOR_AMOUNT]>>,
+
+    /// The modulation targets of the polyphonic aftertouch
+    #[persist = "poly-pressure-targets"]
+    pub poly_pressure_targets: Arc<Mutex<EnvelopeTargets>>,
+    /// The modulation targets of the channel pressure
+    #[persist = "channel-pressure-targets"]
+    pub channel_pressure_targets: Arc<Mutex<EnvelopeTargets>>,
+    /// The modulation targets of the MPE timbre (CC74)
+    #[persist = "timbre-targets"]
+    pub timbre_targets: Arc<Mutex<EnvelopeTargets>>,
+
     #[id = "volume"]
     pub volume: FloatParam,
+    /// How far the notes are spread across the stereo field
+    #[id = "spread"]
+    pub stereo_spread: FloatParam,
+
+    #[id = "polyphony"]
+    pub polyphony: IntParam,
+    #[id = "stealing"]
+    pub voice_stealing: EnumParam<VoiceStealing>,
+    #[id = "voicemode"]
+    pub voice_mode: EnumParam<VoiceMode>,
+    #[id = "priority"]
+    pub note_priority: EnumParam<NotePriority>,
+    #[id = "glide"]
+    pub glide_time: FloatParam,
+    #[id = "glidemode"]
+    pub glide_mode: EnumParam<GlideMode>,
+    #[id = "bendup"]
+    pub bend_up: IntParam,
+    #[id = "benddown"]
+    pub bend_down: IntParam,
 
     #[nested(array, group = "Oscillator Parameters")]
     pub oscillator_params: [OscillatorParams; OSCILLATOR_AMOUNT],
 
     #[nested(array, group = "Envelope Parameters")]
     pub envelope_params: [EnvelopeParams; ENVELOPE_AMOUNT],
+
+    #[nested(id_prefix = "flt", group = "Filter Parameters")]
+    pub filter_params: FilterParams,
+
+    #[nested(id_prefix = "sub", group = "Sub Parameters")]
+    pub sub_params: SubParams,
+
+    #[nested(array, group = "LFO Parameters")]
+    pub lfo_params: [LfoParams; LFO_AMOUNT],
+
+    #[nested(array, group = "CC Parameters")]
+    pub cc_params: [CcParams; CC_SOURCE_AMOUNT],
+
+    #[nested(id_prefix = "mpe", group = "MPE Parameters")]
+    pub mpe_params: MpeParams,
 }
 
 impl Default for SynthParams {
@@ -36,6 +110,14 @@ impl Default for SynthParams {
         Self {
             editor_state: gui::default_state(),
 
+            wavetable_paths: Arc::new(Mutex::new(Default::default())),
+            sample_paths: Arc::new(Mutex::new(Default::default())),
+            harmonics: Arc::new(Mutex::new(std::array::from_fn(|_| default_harmonics()))),
+
+            poly_pressure_targets: Arc::new(Mutex::new(EnvelopeTargets::default())),
+            channel_pressure_targets: Arc::new(Mutex::new(EnvelopeTargets::default())),
+            timbre_targets: Arc::new(Mutex::new(EnvelopeTargets::default())),
+
             volume: FloatParam::new(
                 "Volume",
                 -10.0,
@@ -47,6 +129,44 @@ impl Default for SynthParams {
             ).with_smoother(SmoothingStyle::Logarithmic(3.0))
                 .with_step_size(0.01)
                 .with_unit(" dB"),
+            stereo_spread: FloatParam::new(
+                "Stereo spread",
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_unit(" %")
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            polyphony: IntParam::new(
+                "Polyphony",
+                MAX_POLYPHONY as i32,
+                IntRange::Linear { min: 1, max: MAX_POLYPHONY as i32 },
+            ).with_unit(" voices"),
+            voice_stealing: EnumParam::new("Voice stealing", VoiceStealing::Oldest),
+            voice_mode: EnumParam::new("Voice mode", VoiceMode::Poly),
+            note_priority: EnumParam::new("Note priority", NotePriority::Last),
+            glide_time: FloatParam::new(
+                "Glide",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_step_size(0.001)
+                .with_unit(" sec"),
+            glide_mode: EnumParam::new("Glide mode", GlideMode::ConstantTime),
+            bend_up: IntParam::new(
+                "Bend up",
+                2,
+                IntRange::Linear { min: 0, max: 48 },
+            ).with_unit(" st"),
+            bend_down: IntParam::new(
+                "Bend down",
+                2,
+                IntRange::Linear { min: 0, max: 48 },
+            ).with_unit(" st"),
 
             oscillator_params: get_oscillator_array().map(|i| {
                 OscillatorParams::new(i)
@@ -54,7 +174,18 @@ impl Default for SynthParams {
 
             envelope_params: get_envelope_array().map(|i| {
                 EnvelopeParams::new(i)
-            })
+            }),
+
+            filter_params: FilterParams::default(),
+            sub_params: SubParams::default(),
+
+            lfo_params: get_lfo_array().map(|i| {
+                LfoParams::new(i)
+            }),
+
+            cc_params: DEFAULT_CCS.map(CcParams::new),
+
+            mpe_params: MpeParams::default(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex};
+use nih_plug::prelude::*;
+use crate::params::envelope_target::EnvelopeTargets;
+
+/// The CCs that are used as modulation sources by default: mod wheel, breath, expression and
+/// general purpose 1
+pub const DEFAULT_CCS: [u8; 4] = [1, 2, 11, 16];
+
+#[derive(Params)]
+pub struct CcParams {
+    /// The MIDI CC number that is used as modulation source
+    #[persist = "cc"]
+    pub cc: Arc<Mutex<u8>>,
+
+    #[persist = "cc-targets"]
+    pub targets: Arc<Mutex<EnvelopeTargets>>,
+}
+
+impl CcParams {
+    pub fn new(cc: u8) -> Self {
+        Self {
+            cc: Arc::new(Mutex::new(cc)),
+            targets: Arc::new(Mutex::new(EnvelopeTargets::default())),
+        }
+    }
+}
+
+/// Get the name of well known CCs
+pub fn get_cc_name(cc: u8) -> &'static str {
+    match cc {
+        1 => "Mod wheel",
+        2 => "Breath",
+        4 => "Foot",
+        7 => "Volume",
+        10 => "Pan",
+        11 => "Expression",
+        64 => "Sustain",
+        66 => "Sostenuto",
+        74 => "Brightness",
+        _ => "",
+    }
+}
@@ -17,6 +17,14 @@ pub struct EnvelopeParams {
     #[id = "r"]
     pub release: FloatParam,
 
+    /// The shape of each segment, from logarithmic (-1) through linear (0) to exponential (1)
+    #[id = "ac"]
+    pub attack_curve: FloatParam,
+    #[id = "dc"]
+    pub decay_curve: FloatParam,
+    #[id = "rc"]
+    pub release_curve: FloatParam,
+
     // TODO test if this properly persists in a plugin host
     #[persist = "targets"]
     pub targets: Arc<Mutex<EnvelopeTargets>>,
@@ -78,6 +86,10 @@ impl EnvelopeParams {
                 .with_step_size(0.01)
                 .with_unit(" sec"),
 
+            attack_curve: curve_param(format!("ENV{index} Attack Curve")),
+            decay_curve: curve_param(format!("ENV{index} Decay Curve")),
+            release_curve: curve_param(format!("ENV{index} Release Curve")),
+
             targets: Arc::new(Mutex::new(
                 if index == 0 {
                     EnvelopeTargets::with_target(Target::AllOscillators)
@@ -89,6 +101,18 @@ impl EnvelopeParams {
     }
 }
 
+fn curve_param(name: String) -> FloatParam {
+    FloatParam::new(
+        name,
+        0.0,
+        FloatRange::Linear {
+            min: -1.0,
+            max: 1.0,
+        },
+    ).with_smoother(SmoothingStyle::Linear(3.0))
+        .with_step_size(0.01)
+}
+
 impl Default for EnvelopeParams {
     fn default() -> Self {
         Self::new(0)
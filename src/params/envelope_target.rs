@@ -1,9 +1,10 @@
 use std::fmt::{Display, Formatter};
-use std::sync::MutexGuard;
+use std::sync::{Arc, Mutex, MutexGuard};
 use nih_plug_vizia::vizia::prelude::*;
 use serde::{Deserialize, Serialize};
 use crate::OSCILLATOR_AMOUNT;
 use crate::gui::events::ControlEvent;
+use crate::params::SynthParams;
 
 #[derive(Serialize, Deserialize, Lens, Clone)]
 pub struct EnvelopeTargets {
@@ -37,14 +38,6 @@ impl EnvelopeTargets {
     pub fn remove(&mut self, index: usize) -> (Target, f32) {
         self.targets.remove(index)
     }
-
-    // TODO use?
-    pub fn get_amount_for(&self, target: Target) -> f32 {
-        self.targets.iter().filter_map(|(t, d)|{
-            if *t == target { Some(d) }
-            else { None }
-        }).sum()
-    }
 }
 
 impl Default for EnvelopeTargets {
@@ -58,10 +51,10 @@ impl Model for EnvelopeTargets {
     fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
         event.map(|control_event: &ControlEvent, _meta|
             match control_event {
-                ControlEvent::AddEnvelopeTarget => {
+                ControlEvent::AddTarget => {
                     self.add();
                 }
-                ControlEvent::RemoveEnvelopeTarget(i) => {
+                ControlEvent::RemoveTarget(i) => {
                     self.remove(*i);
                 }
                 _ => {}
@@ -70,6 +63,30 @@ impl Model for EnvelopeTargets {
     }
 }
 
+/// Something that modulates its list of [`EnvelopeTargets`]
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ModulationSource {
+    Envelope(usize),
+    Lfo(usize),
+    Cc(usize),
+    PolyPressure,
+    ChannelPressure,
+    Timbre,
+}
+
+impl ModulationSource {
+    pub fn targets<'a>(&self, params: &'a SynthParams) -> &'a Arc<Mutex<EnvelopeTargets>> {
+        match *self {
+            ModulationSource::Envelope(i) => &params.envelope_params[i].targets,
+            ModulationSource::Lfo(i) => &params.lfo_params[i].targets,
+            ModulationSource::Cc(i) => &params.cc_params[i].targets,
+            ModulationSource::PolyPressure => &params.poly_pressure_targets,
+            ModulationSource::ChannelPressure => &params.channel_pressure_targets,
+            ModulationSource::Timbre => &params.timbre_targets,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Target {
     None,
@@ -84,7 +101,10 @@ impl Display for Target {
             Target::None => write!(f, "-"),
             Target::AllOscillators => write!(f, "All oscillators"),
             Target::Oscillator(i) => write!(f, "Oscillator {i}"),
-            Target::Parameter(i) => write!(f, "Parameter {i}"),
+            Target::Parameter(i) => match ModParameter::from_index(*i) {
+                Some(parameter) => write!(f, "{parameter}"),
+                None => write!(f, "Unknown parameter"),
+            },
         }
     }
 }
@@ -94,7 +114,91 @@ pub fn get_possible_targets() -> Vec<Target> {
     for i in 0..OSCILLATOR_AMOUNT {
         result.push(Target::Oscillator(i));
     }
-    // TODO parameters
+    for parameter in ModParameter::all() {
+        result.push(Target::Parameter(parameter.index()));
+    }
 
     result
+}
+
+/// Global parameters use the indices below this, every oscillator gets a block of this size after
+const OSCILLATOR_PARAMETER_BLOCK: usize = 0x100;
+
+/// A parameter that can be modulated using [`Target::Parameter`]. Every parameter has a fixed
+/// index, so saved targets keep pointing to the same parameter when new ones are added.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ModParameter {
+    FilterCutoff,
+    FilterResonance,
+    OscillatorVolume(usize),
+    OscillatorDetune(usize),
+    OscillatorPulseWidth(usize),
+    OscillatorPitch(usize),
+}
+
+impl ModParameter {
+    pub fn index(&self) -> usize {
+        match *self {
+            ModParameter::FilterCutoff => 0,
+            ModParameter::FilterResonance => 1,
+            ModParameter::OscillatorVolume(i) => (i + 1) * OSCILLATOR_PARAMETER_BLOCK,
+            ModParameter::OscillatorDetune(i) => (i + 1) * OSCILLATOR_PARAMETER_BLOCK + 1,
+            ModParameter::OscillatorPulseWidth(i) => (i + 1) * OSCILLATOR_PARAMETER_BLOCK + 2,
+            ModParameter::OscillatorPitch(i) => (i + 1) * OSCILLATOR_PARAMETER_BLOCK + 3,
+        }
+    }
+
+    /// The ID used for polyphonic modulation by the host (CLAP)
+    pub fn poly_modulation_id(&self) -> u32 {
+        self.index() as u32
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        if index < OSCILLATOR_PARAMETER_BLOCK {
+            return match index {
+                0 => Some(ModParameter::FilterCutoff),
+                1 => Some(ModParameter::FilterResonance),
+                _ => None,
+            };
+        }
+
+        let oscillator = index / OSCILLATOR_PARAMETER_BLOCK - 1;
+        if oscillator >= OSCILLATOR_AMOUNT {
+            return None;
+        }
+        match index % OSCILLATOR_PARAMETER_BLOCK {
+            0 => Some(ModParameter::OscillatorVolume(oscillator)),
+            1 => Some(ModParameter::OscillatorDetune(oscillator)),
+            2 => Some(ModParameter::OscillatorPulseWidth(oscillator)),
+            3 => Some(ModParameter::OscillatorPitch(oscillator)),
+            _ => None,
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        let mut result = Vec::new();
+        for i in 0..OSCILLATOR_AMOUNT {
+            result.push(ModParameter::OscillatorPitch(i));
+            result.push(ModParameter::OscillatorVolume(i));
+            result.push(ModParameter::OscillatorDetune(i));
+            result.push(ModParameter::OscillatorPulseWidth(i));
+        }
+        result.push(ModParameter::FilterCutoff);
+        result.push(ModParameter::FilterResonance);
+
+        result
+    }
+}
+
+impl Display for ModParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModParameter::FilterCutoff => write!(f, "Filter cutoff"),
+            ModParameter::FilterResonance => write!(f, "Filter resonance"),
+            ModParameter::OscillatorVolume(i) => write!(f, "OSC{i} Volume"),
+            ModParameter::OscillatorDetune(i) => write!(f, "OSC{i} Detune"),
+            ModParameter::OscillatorPulseWidth(i) => write!(f, "OSC{i} PW"),
+            ModParameter::OscillatorPitch(i) => write!(f, "OSC{i} Pitch"),
+        }
+    }
 }
\ No newline at end of file
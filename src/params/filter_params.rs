@@ -0,0 +1,61 @@
+use nih_plug::prelude::*;
+use crate::process::filter::FilterMode;
+
+#[derive(Params)]
+pub struct FilterParams {
+    #[id = "on"]
+    pub enabled: BoolParam,
+
+    #[id = "mode"]
+    pub mode: EnumParam<FilterMode>,
+
+    #[id = "cut"]
+    pub cutoff: FloatParam,
+
+    #[id = "res"]
+    pub resonance: FloatParam,
+
+    #[id = "key"]
+    pub keytrack: FloatParam,
+}
+
+impl Default for FilterParams {
+    fn default() -> Self {
+        Self {
+            enabled: BoolParam::new("Filter Enabled", false),
+
+            mode: EnumParam::new("Filter Mode", FilterMode::Lowpass),
+
+            cutoff: FloatParam::new(
+                "Filter Cutoff",
+                2_000.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20_000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_smoother(SmoothingStyle::Logarithmic(10.0))
+                .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+                .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            resonance: FloatParam::new(
+                "Filter Resonance",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.01),
+
+            keytrack: FloatParam::new(
+                "Filter Key Tracking",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_step_size(0.01),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use enum_iterator::Sequence;
+use nih_plug::prelude::*;
+use crate::OSCILLATOR_AMOUNT;
+
+/// How much one oscillator modulates the phase of another oscillator
+#[derive(Params)]
+pub struct FmParams {
+    /// The modulation index, the largest phase change in radians
+    #[id = "index"]
+    pub index: FloatParam,
+}
+
+impl FmParams {
+    pub fn new(carrier: usize, modulator: usize) -> Self {
+        Self {
+            index: FloatParam::new(
+                format!("OSC{modulator} to OSC{carrier} FM"),
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 10.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.01),
+        }
+    }
+}
+
+/// The index that the algorithms use for every connection
+const ALGORITHM_INDEX: f32 = 2.0;
+/// The index that the algorithms use for feedback
+const ALGORITHM_FEEDBACK: f32 = 1.0;
+
+/// Presets for the FM matrix, which set how the oscillators modulate each other and which of them
+/// are heard
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum FmAlgorithm {
+    /// No modulation, all oscillators are heard
+    #[id = "parallel"]
+    #[name = "Parallel"]
+    Parallel,
+    /// OSC3 modulates OSC2, which modulates OSC1, which modulates OSC0
+    #[id = "stack"]
+    #[name = "Stack"]
+    Stack,
+    /// The same as [`FmAlgorithm::Stack`], with feedback on OSC3
+    #[id = "stackfb"]
+    #[name = "Stack FB"]
+    StackFeedback,
+    /// OSC1 modulates OSC0 and OSC3 modulates OSC2
+    #[id = "twostacks"]
+    #[name = "2 Stacks"]
+    TwoStacks,
+    /// OSC1, OSC2 and OSC3 all modulate OSC0
+    #[id = "threetoone"]
+    #[name = "3 to 1"]
+    ThreeToOne,
+    /// OSC3 modulates OSC0, OSC1 and OSC2
+    #[id = "onetothree"]
+    #[name = "1 to 3"]
+    OneToThree,
+}
+
+impl FmAlgorithm {
+    /// The modulator, carrier and index of every connection
+    fn connections(&self) -> &'static [(usize, usize, f32)] {
+        match self {
+            FmAlgorithm::Parallel => &[],
+            FmAlgorithm::Stack => &[
+                (3, 2, ALGORITHM_INDEX), (2, 1, ALGORITHM_INDEX), (1, 0, ALGORITHM_INDEX),
+            ],
+            FmAlgorithm::StackFeedback => &[
+                (3, 3, ALGORITHM_FEEDBACK),
+                (3, 2, ALGORITHM_INDEX), (2, 1, ALGORITHM_INDEX), (1, 0, ALGORITHM_INDEX),
+            ],
+            FmAlgorithm::TwoStacks => &[(1, 0, ALGORITHM_INDEX), (3, 2, ALGORITHM_INDEX)],
+            FmAlgorithm::ThreeToOne => &[
+                (1, 0, ALGORITHM_INDEX), (2, 0, ALGORITHM_INDEX), (3, 0, ALGORITHM_INDEX),
+            ],
+            FmAlgorithm::OneToThree => &[
+                (3, 0, ALGORITHM_INDEX), (3, 1, ALGORITHM_INDEX), (3, 2, ALGORITHM_INDEX),
+            ],
+        }
+    }
+
+    /// The index of every connection, as `matrix[carrier][modulator]`
+    pub fn matrix(&self) -> [[f32; OSCILLATOR_AMOUNT]; OSCILLATOR_AMOUNT] {
+        let mut matrix = [[0.0; OSCILLATOR_AMOUNT]; OSCILLATOR_AMOUNT];
+        for (modulator, carrier, index) in self.connections() {
+            matrix[*carrier][*modulator] = *index;
+        }
+        matrix
+    }
+
+    /// Which oscillators are heard
+    pub fn outputs(&self) -> [bool; OSCILLATOR_AMOUNT] {
+        match self {
+            FmAlgorithm::Parallel => [true; OSCILLATOR_AMOUNT],
+            FmAlgorithm::TwoStacks => [true, false, true, false],
+            FmAlgorithm::OneToThree => [true, true, true, false],
+            FmAlgorithm::Stack | FmAlgorithm::StackFeedback | FmAlgorithm::ThreeToOne => {
+                [true, false, false, false]
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::{Arc, Mutex};
+use nih_plug::prelude::*;
+use crate::params::Enable;
+use crate::params::envelope_target::EnvelopeTargets;
+use crate::process::lfo::{LfoDivision, LfoRetrigger, LfoShape};
+
+#[derive(Params)]
+pub struct LfoParams {
+    #[id = "lfoon"]
+    pub enabled: BoolParam,
+
+    #[id = "lfowave"]
+    pub wave_kind: EnumParam<LfoShape>,
+
+    #[id = "rate"]
+    pub rate: FloatParam,
+
+    #[id = "sync"]
+    pub sync: BoolParam,
+
+    #[id = "div"]
+    pub division: EnumParam<LfoDivision>,
+
+    #[id = "depth"]
+    pub depth: FloatParam,
+
+    #[id = "phase"]
+    pub phase: FloatParam,
+
+    #[id = "retrig"]
+    pub retrigger: EnumParam<LfoRetrigger>,
+
+    #[persist = "lfo-targets"]
+    pub targets: Arc<Mutex<EnvelopeTargets>>,
+}
+
+impl LfoParams {
+    pub fn new(index: usize) -> Self {
+        Self {
+            enabled: BoolParam::new(format!("LFO{index} Enabled"), false),
+
+            wave_kind: EnumParam::new(format!("LFO{index} Wave"), LfoShape::Sine),
+
+            rate: FloatParam::new(
+                format!("LFO{index} Rate"),
+                2.0,
+                FloatRange::Skewed {
+                    min: 0.01,
+                    max: 20.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.01)
+                .with_unit(" Hz"),
+
+            sync: BoolParam::new(format!("LFO{index} Sync"), false),
+
+            division: EnumParam::new(format!("LFO{index} Division"), LfoDivision::Quarter),
+
+            depth: FloatParam::new(
+                format!("LFO{index} Depth"),
+                1.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.01),
+
+            phase: FloatParam::new(
+                format!("LFO{index} Phase"),
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_step_size(0.01),
+
+            retrigger: EnumParam::new(format!("LFO{index} Retrigger"), LfoRetrigger::Voice),
+
+            targets: Arc::new(Mutex::new(EnvelopeTargets::default())),
+        }
+    }
+}
+
+impl Default for LfoParams {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Enable for LfoParams {
+    fn enabled(&self) -> &BoolParam {
+        &self.enabled
+    }
+}
@@ -0,0 +1,39 @@
+use nih_plug::prelude::*;
+use crate::process::mpe::MpeZone;
+
+#[derive(Params)]
+pub struct MpeParams {
+    #[id = "on"]
+    pub enabled: BoolParam,
+
+    #[id = "zone"]
+    pub zone: EnumParam<MpeZone>,
+
+    #[id = "members"]
+    pub member_channels: IntParam,
+
+    #[id = "bend"]
+    pub bend_range: IntParam,
+}
+
+impl Default for MpeParams {
+    fn default() -> Self {
+        Self {
+            enabled: BoolParam::new("MPE Enabled", false),
+
+            zone: EnumParam::new("MPE Zone", MpeZone::Lower),
+
+            member_channels: IntParam::new(
+                "MPE Member Channels",
+                15,
+                IntRange::Linear { min: 1, max: 15 },
+            ),
+
+            bend_range: IntParam::new(
+                "MPE Bend Range",
+                48,
+                IntRange::Linear { min: 1, max: 96 },
+            ).with_unit(" st"),
+        }
+    }
+}
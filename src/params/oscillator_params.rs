@@ -1,6 +1,12 @@
 use nih_plug::prelude::*;
 use crate::params::Enable;
-use crate::process::note::WaveKind;
+use crate::params::fm_params::FmParams;
+use crate::params::sample_params::SampleParams;
+use crate::params::envelope_target::ModParameter;
+use crate::process::noise::NoiseColor;
+use crate::process::note::{CombineMode, WaveKind};
+use crate::process::unison::DetuneCurve;
+use crate::{MAX_UNISON, OSCILLATOR_AMOUNT};
 
 #[derive(Params)]
 pub struct OscillatorParams {
@@ -10,9 +16,20 @@ pub struct OscillatorParams {
     #[id = "pwm"]
     pub pulse_width: FloatParam,
 
+    #[id = "noise"]
+    pub noise_color: EnumParam<NoiseColor>,
+
     #[id = "on"]
     pub enabled: BoolParam,
 
+    /// Whether this oscillator is heard, oscillators that only modulate others can be turned off
+    #[id = "out"]
+    pub output: BoolParam,
+
+    /// How this oscillator is combined with the previous one
+    #[id = "combine"]
+    pub combine: EnumParam<CombineMode>,
+
     #[id = "vol"]
     pub volume: FloatParam,
 
@@ -21,6 +38,48 @@ pub struct OscillatorParams {
 
     #[id = "det"]
     pub detune: FloatParam,
+
+    #[id = "pan"]
+    pub pan: FloatParam,
+
+    #[id = "uni"]
+    pub unison_voices: IntParam,
+
+    #[id = "unidet"]
+    pub unison_detune: FloatParam,
+
+    #[id = "unicurve"]
+    pub unison_curve: EnumParam<DetuneCurve>,
+
+    #[id = "uniwidth"]
+    pub unison_width: FloatParam,
+
+    #[id = "uniblend"]
+    pub unison_blend: FloatParam,
+
+    #[id = "uniphase"]
+    pub unison_random_phase: BoolParam,
+
+    #[id = "aa"]
+    pub antialias: BoolParam,
+
+    #[id = "wtpos"]
+    pub wavetable_position: FloatParam,
+
+    /// Fades out the even harmonics (-1) or the odd harmonics (1) of the additive wave
+    #[id = "addbal"]
+    pub additive_balance: FloatParam,
+
+    /// Makes the higher harmonics of the additive wave quieter or louder, in dB per octave
+    #[id = "addtilt"]
+    pub additive_tilt: FloatParam,
+
+    #[nested(id_prefix = "smp", group = "Sample")]
+    pub sample: SampleParams,
+
+    /// How much every oscillator modulates the phase of this oscillator
+    #[nested(array, group = "FM")]
+    pub fm: [FmParams; OSCILLATOR_AMOUNT],
 }
 
 impl OscillatorParams {
@@ -36,10 +95,17 @@ impl OscillatorParams {
                     max: 1.0,
                 },
             ).with_smoother(SmoothingStyle::Linear(1.0))
+                .with_poly_modulation_id(ModParameter::OscillatorPulseWidth(index).poly_modulation_id())
                 .with_step_size(0.01),
 
+            noise_color: EnumParam::new(format!("OSC{index} Noise Color"), NoiseColor::White),
+
             enabled: BoolParam::new(format!("OSC{index} Enabled"), index == 0),
 
+            output: BoolParam::new(format!("OSC{index} Output"), true),
+
+            combine: EnumParam::new(format!("OSC{index} Combine"), CombineMode::Mix),
+
             volume: FloatParam::new(
                 format!("OSC{index} Volume"),
                 -0.01,
@@ -49,6 +115,7 @@ impl OscillatorParams {
                     factor: FloatRange::skew_factor(1.0),
                 },
             ).with_smoother(SmoothingStyle::Logarithmic(3.0))
+                .with_poly_modulation_id(ModParameter::OscillatorVolume(index).poly_modulation_id())
                 .with_step_size(0.01)
                 .with_unit(" dB"),
 
@@ -68,8 +135,104 @@ impl OscillatorParams {
                     min: -100.0,
                     max: 100.0,
                 }
+            ).with_poly_modulation_id(ModParameter::OscillatorDetune(index).poly_modulation_id())
+                .with_step_size(0.1)
+                .with_unit(" cents"),
+
+            pan: FloatParam::new(
+                format!("OSC{index} Pan"),
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_value_to_string(formatters::v2s_f32_panning())
+                .with_string_to_value(formatters::s2v_f32_panning()),
+
+            unison_voices: IntParam::new(
+                format!("OSC{index} Unison"),
+                1,
+                IntRange::Linear {
+                    min: 1,
+                    max: MAX_UNISON as i32,
+                },
+            ),
+
+            unison_detune: FloatParam::new(
+                format!("OSC{index} Unison Detune"),
+                20.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
             ).with_step_size(0.1)
                 .with_unit(" cents"),
+
+            unison_curve: EnumParam::new(format!("OSC{index} Unison Curve"), DetuneCurve::Linear),
+
+            unison_width: FloatParam::new(
+                format!("OSC{index} Unison Width"),
+                1.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage())
+                .with_unit(" %"),
+
+            unison_blend: FloatParam::new(
+                format!("OSC{index} Unison Blend"),
+                0.5,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_value_to_string(formatters::v2s_f32_percentage(0))
+                .with_string_to_value(formatters::s2v_f32_percentage())
+                .with_unit(" %"),
+
+            unison_random_phase: BoolParam::new(format!("OSC{index} Unison Random Phase"), true),
+
+            antialias: BoolParam::new(format!("OSC{index} Anti-aliasing"), true),
+
+            wavetable_position: FloatParam::new(
+                format!("OSC{index} Wavetable Position"),
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.01),
+
+            additive_balance: FloatParam::new(
+                format!("OSC{index} Odd/Even Balance"),
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.01),
+
+            additive_tilt: FloatParam::new(
+                format!("OSC{index} Spectral Tilt"),
+                0.0,
+                FloatRange::Linear {
+                    min: -12.0,
+                    max: 12.0,
+                },
+            ).with_smoother(SmoothingStyle::Linear(10.0))
+                .with_step_size(0.1)
+                .with_unit(" dB/oct"),
+
+            sample: SampleParams::new(index),
+
+            fm: std::array::from_fn(|modulator| FmParams::new(index, modulator)),
         }
     }
 }
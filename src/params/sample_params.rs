@@ -0,0 +1,75 @@
+use nih_plug::prelude::*;
+use crate::process::sample::SampleMode;
+
+/// How an oscillator plays its sample when it uses the sample wave
+#[derive(Params)]
+pub struct SampleParams {
+    /// The note at which the sample plays at its original speed
+    #[id = "root"]
+    pub root_key: IntParam,
+
+    #[id = "start"]
+    pub start: FloatParam,
+
+    #[id = "mode"]
+    pub mode: EnumParam<SampleMode>,
+
+    #[id = "loopstart"]
+    pub loop_start: FloatParam,
+
+    #[id = "loopend"]
+    pub loop_end: FloatParam,
+
+    /// The length of the crossfade at the end of the loop, relative to the loop length
+    #[id = "xfade"]
+    pub crossfade: FloatParam,
+}
+
+impl SampleParams {
+    pub fn new(index: usize) -> Self {
+        let position = |name: &str, default: f32| {
+            FloatParam::new(
+                format!("OSC{index} Sample {name}"),
+                default,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ).with_value_to_string(formatters::v2s_f32_percentage(1))
+                .with_string_to_value(formatters::s2v_f32_percentage())
+                .with_unit(" %")
+        };
+
+        Self {
+            root_key: IntParam::new(
+                format!("OSC{index} Sample Root Key"),
+                60,
+                IntRange::Linear {
+                    min: 0,
+                    max: 127,
+                },
+            ).with_value_to_string(formatters::v2s_i32_note_formatter())
+                .with_string_to_value(formatters::s2v_i32_note_formatter()),
+
+            start: position("Start", 0.0),
+
+            mode: EnumParam::new(format!("OSC{index} Sample Mode"), SampleMode::OneShot),
+
+            loop_start: position("Loop Start", 0.0),
+
+            loop_end: position("Loop End", 1.0),
+
+            // Half of the loop at most, because the end of the loop fades into its start
+            crossfade: FloatParam::new(
+                format!("OSC{index} Sample Crossfade"),
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 0.5,
+                },
+            ).with_value_to_string(formatters::v2s_f32_percentage(1))
+                .with_string_to_value(formatters::s2v_f32_percentage())
+                .with_unit(" %"),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use nih_plug::prelude::*;
+use crate::process::sub_oscillator::SubShape;
+
+#[derive(Params)]
+pub struct SubParams {
+    #[id = "on"]
+    pub enabled: BoolParam,
+
+    #[id = "shape"]
+    pub shape: EnumParam<SubShape>,
+
+    /// How many octaves the sub-oscillator is below the played note
+    #[id = "oct"]
+    pub octaves: IntParam,
+
+    #[id = "vol"]
+    pub volume: FloatParam,
+}
+
+impl Default for SubParams {
+    fn default() -> Self {
+        Self {
+            enabled: BoolParam::new("Sub Enabled", false),
+
+            shape: EnumParam::new("Sub Shape", SubShape::Sine),
+
+            octaves: IntParam::new(
+                "Sub Octaves",
+                1,
+                IntRange::Linear {
+                    min: 1,
+                    max: 2,
+                },
+            ),
+
+            volume: FloatParam::new(
+                "Sub Volume",
+                -6.0,
+                FloatRange::Skewed {
+                    min: util::MINUS_INFINITY_DB,
+                    max: -0.01,
+                    factor: FloatRange::skew_factor(1.0),
+                },
+            ).with_smoother(SmoothingStyle::Logarithmic(3.0))
+                .with_step_size(0.01)
+                .with_unit(" dB"),
+        }
+    }
+}
@@ -2,3 +2,15 @@ pub mod notes;
 pub mod visual_data;
 pub mod note;
 pub mod envelope;
+pub mod polyblep;
+pub mod wavetable;
+pub mod voice;
+pub mod filter;
+pub mod modulation;
+pub mod lfo;
+pub mod mpe;
+pub mod unison;
+pub mod noise;
+pub mod sub_oscillator;
+pub mod sample;
+pub mod additive;
\ No newline at end of file
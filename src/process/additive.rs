@@ -0,0 +1,86 @@
+use std::f32::consts;
+use nih_plug::util;
+use crate::{MAX_HARMONICS, OSCILLATOR_AMOUNT};
+use crate::params::SynthParams;
+use crate::utils::f32x4::F32x4;
+
+/// The amplitude of every harmonic of every oscillator, as drawn in the GUI
+#[derive(Clone, Copy)]
+pub struct Harmonics(pub [[f32; MAX_HARMONICS]; OSCILLATOR_AMOUNT]);
+
+impl Harmonics {
+    pub fn from_params(params: &SynthParams) -> Self {
+        let harmonics = params.harmonics.lock().expect("Cannot lock harmonics");
+        Self(std::array::from_fn(|i| {
+            std::array::from_fn(|n| harmonics[i].get(n).copied().unwrap_or(0.0))
+        }))
+    }
+}
+
+impl Default for Harmonics {
+    fn default() -> Self {
+        let mut harmonics = [0.0; MAX_HARMONICS];
+        harmonics[0] = 1.0;
+        Self([harmonics; OSCILLATOR_AMOUNT])
+    }
+}
+
+/// The harmonics of a new oscillator, which is only the fundamental (a sine)
+pub fn default_harmonics() -> Vec<f32> {
+    Harmonics::default().0[0].to_vec()
+}
+
+/// Apply the odd/even `balance` (from -1, only odd, to 1, only even) and the spectral `tilt` (in
+/// dB per octave) to the `harmonics`. The result is scaled down when the amplitudes add up to more
+/// than 1, so the oscillator cannot clip.
+pub fn get_amplitudes(harmonics: &[f32; MAX_HARMONICS], balance: f32, tilt: f32) -> [f32; MAX_HARMONICS] {
+    let mut amplitudes = [0.0; MAX_HARMONICS];
+    for (n, (amplitude, harmonic)) in amplitudes.iter_mut().zip(harmonics).enumerate() {
+        let number = n + 1;
+        // The fundamental is always kept
+        let balance_gain = if number == 1 {
+            1.0
+        } else if number % 2 == 1 {
+            (1.0 - balance).min(1.0)
+        } else {
+            (1.0 + balance).min(1.0)
+        };
+        *amplitude = harmonic * balance_gain * util::db_to_gain(tilt * (number as f32).log2());
+    }
+
+    let sum: f32 = amplitudes.iter().sum();
+    if sum > 1.0 {
+        for amplitude in &mut amplitudes {
+            *amplitude /= sum;
+        }
+    }
+    amplitudes
+}
+
+/// Sum the harmonics with the given `amplitudes` for every lane. Harmonics above the Nyquist
+/// frequency are left out, so the result does not alias.
+pub fn get_additive_lanes(phase: F32x4, phase_delta: F32x4, amplitudes: &[f32; MAX_HARMONICS]) -> F32x4 {
+    let angle = phase * consts::TAU;
+    let twice_cos = angle.map(f32::cos) * 2.0;
+    let mut previous = F32x4::ZERO;
+    let mut current = angle.map(f32::sin);
+
+    let mut sum = F32x4::ZERO;
+    for (n, amplitude) in amplitudes.iter().enumerate() {
+        let frequency = phase_delta * (n + 1) as f32;
+        if frequency.0.iter().all(|frequency| *frequency >= 0.5) { break; }
+
+        if *amplitude != 0.0 {
+            let audible = F32x4::from_fn(|lane| {
+                if frequency.0[lane] < 0.5 { *amplitude } else { 0.0 }
+            });
+            sum += current * audible;
+        }
+
+        // sin((n + 1) * x) = 2 * cos(x) * sin(n * x) - sin((n - 1) * x)
+        let next = twice_cos * current - previous;
+        previous = current;
+        current = next;
+    }
+    sum
+}
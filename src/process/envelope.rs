@@ -1,32 +1,8 @@
-use std::sync::{Arc, Mutex};
-use crate::params::envelope_target::{EnvelopeTargets, Target};
+use crate::ENVELOPE_AMOUNT;
+use crate::utils::f32x4::{F32x4, LANES};
 
-#[derive(Clone)]
-pub struct EnvelopeProperties {
-    pub adsr: Adsr,
-    pub targets:  Arc<Mutex<EnvelopeTargets>>,
-}
-
-impl EnvelopeProperties {
-    pub fn new(adsr: Adsr, targets: Arc<Mutex<EnvelopeTargets>>) -> Self {
-        Self { adsr, targets }
-    }
-
-    pub fn get_amount_for(&self, target: Target) -> f32 {
-        let targets = self.targets.lock()
-            .expect("Failed to acquire envelope_targets lock");
-        targets.get_amount_for(target)
-    }
-}
-
-impl Default for EnvelopeProperties {
-    fn default() -> Self {
-        Self {
-            adsr: Adsr::default(),
-            targets: Arc::new(Mutex::new(EnvelopeTargets::default())),
-        }
-    }
-}
+/// How far the segments bend at the ends of the curve parameters (-1 and 1)
+const CURVE_STEEPNESS: f32 = 6.0;
 
 #[derive(Clone, Copy, Debug)]
 pub struct Adsr {
@@ -34,11 +10,17 @@ pub struct Adsr {
     decay: f32,
     sustain: f32,
     release: f32,
+    /// The shape of each segment, from -1 (logarithmic) through 0 (linear) to 1 (exponential)
+    attack_curve: f32,
+    decay_curve: f32,
+    release_curve: f32,
 }
 
 impl Adsr {
-    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
-        Self { attack, decay, sustain, release }
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32,
+               attack_curve: f32, decay_curve: f32, release_curve: f32,
+    ) -> Self {
+        Self { attack, decay, sustain, release, attack_curve, decay_curve, release_curve }
     }
 
 
@@ -54,21 +36,127 @@ impl Adsr {
     pub fn release(&self) -> f32 {
         self.release
     }
+
+    /// The gain during an attack that started at gain `from`, at `progress` from 0 (the start)
+    /// to 1 (the end)
+    pub fn attack_gain(&self, from: f32, progress: f32) -> f32 {
+        segment(from, 1.0, progress, self.attack_curve)
+    }
+
+    /// The gain during the decay, at `progress` from 0 to 1
+    pub fn decay_gain(&self, progress: f32) -> f32 {
+        segment(1.0, self.sustain, progress, self.decay_curve)
+    }
+
+    /// The gain during a release that started at gain `from`, at `progress` from 0 to 1
+    pub fn release_gain(&self, from: f32, progress: f32) -> f32 {
+        segment(from, 0.0, progress, self.release_curve)
+    }
+
+    /// The point `time` seconds after the note started, while it is held. The attack starts at
+    /// `attack_from`, which is only above 0 when a sounding note was retriggered.
+    pub fn held_point(&self, attack_from: f32, time: f32) -> SegmentPoint {
+        if time < self.attack {
+            SegmentPoint::new(attack_from, 1.0, time / self.attack, self.attack_curve)
+        } else if time < self.attack + self.decay {
+            SegmentPoint::new(1.0, self.sustain, (time - self.attack) / self.decay, self.decay_curve)
+        } else {
+            SegmentPoint::new(self.sustain, self.sustain, 1.0, 0.0)
+        }
+    }
+
+    /// The point `time` seconds after the note was released, when the envelope was at gain `from`
+    pub fn released_point(&self, from: f32, time: f32) -> SegmentPoint {
+        let progress = if time >= self.release { 1.0 } else { time / self.release };
+        SegmentPoint::new(from, 0.0, progress, self.release_curve)
+    }
 }
 
 impl Default for Adsr {
     fn default() -> Self {
-        Self { attack: 0.01, decay: 0.0, sustain: 1.0, release: 0.01 }
+        Self {
+            attack: 0.01,
+            decay: 0.0,
+            sustain: 1.0,
+            release: 0.01,
+            attack_curve: 0.0,
+            decay_curve: 0.0,
+            release_curve: 0.0,
+        }
     }
 }
 
+/// A point on a segment of an envelope, of which the gain is calculated by [`SegmentPoint::gain`],
+/// or by [`segment_lanes`] for four voices at once
+#[derive(Clone, Copy, Default)]
+pub struct SegmentPoint {
+    from: f32,
+    to: f32,
+    /// From 0 (the start of the segment) to 1 (the end)
+    progress: f32,
+    curve: f32,
+}
+
+impl SegmentPoint {
+    fn new(from: f32, to: f32, progress: f32, curve: f32) -> Self {
+        Self { from, to, progress, curve }
+    }
+
+    pub fn gain(&self) -> f32 {
+        segment(self.from, self.to, self.progress, self.curve)
+    }
+}
+
+/// Move from `from` to `to` at `progress` (0 to 1). Exponential curves move slowly near the lower
+/// level and quickly near the higher level, so rising and falling segments bend the opposite way.
+fn segment(from: f32, to: f32, progress: f32, curve: f32) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    if to >= from {
+        from + (to - from) * apply_curve(progress, curve)
+    } else {
+        to + (from - to) * apply_curve(1.0 - progress, curve)
+    }
+}
+
+/// Bend `t` (0 to 1), a positive `curve` starts slowly and speeds up, a negative `curve` starts
+/// quickly and slows down
+fn apply_curve(t: f32, curve: f32) -> f32 {
+    let k = curve * CURVE_STEEPNESS;
+    if k.abs() < 1e-3 {
+        t
+    } else {
+        (k * t).exp_m1() / k.exp_m1()
+    }
+}
+
+/// The gains at the `points` of four voices, which is the same as [`segment`] for every lane. The
+/// curves, which are the expensive part, are calculated for all lanes at once.
+pub fn segment_lanes(points: &[SegmentPoint; LANES]) -> F32x4 {
+    // Falling segments are mirrored, like in `segment`
+    let low = F32x4::from_fn(|lane| points[lane].from.min(points[lane].to));
+    let span = F32x4::from_fn(|lane| (points[lane].to - points[lane].from).abs());
+    let t = F32x4::from_fn(|lane| {
+        let point = points[lane];
+        let progress = point.progress.clamp(0.0, 1.0);
+        if point.to >= point.from { progress } else { 1.0 - progress }
+    });
+
+    let k = F32x4::from_fn(|lane| points[lane].curve * CURVE_STEEPNESS);
+    let curved = ((k * t).exp() - 1.0) / (k.exp() - 1.0);
+    // Nearly linear curves would divide by almost 0
+    let curved = F32x4::from_fn(|lane| if k.0[lane].abs() < 1e-3 { t.0[lane] } else { curved.0[lane] });
+
+    low + span * curved
+}
+
 #[derive(PartialEq, Clone)]
 pub enum Stage {
     Held,
     Released {
         released_at: f32,
-        gain_before: f32,
+        /// The gain of every envelope when the note was released, which is where the release
+        /// starts, even if the envelope parameters change afterwards
+        from: [f32; ENVELOPE_AMOUNT],
     },
     Finished,
 }
-
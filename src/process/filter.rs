@@ -0,0 +1,117 @@
+use std::f32::consts;
+use enum_iterator::Sequence;
+use crate::process::modulation::CUTOFF_RANGE;
+
+/// A state variable filter using the topology-preserving transform, see
+/// https://cytomic.com/files/dsp/SvfLinearTrapOptimised2.pdf
+#[derive(Default)]
+pub struct Filter {
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl Filter {
+    pub fn process(&mut self, input: f32, coefficients: &FilterCoefficients) -> f32 {
+        let FilterCoefficients { mode, k, a1, a2, a3 } = *coefficients;
+
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        match mode {
+            FilterMode::Lowpass => v2,
+            FilterMode::Highpass => input - k * v1 - v2,
+            FilterMode::Bandpass => v1,
+            FilterMode::Notch => input - k * v1,
+        }
+    }
+}
+
+/// The coefficients of the [`Filter`], which only have to be calculated once per block
+#[derive(Clone, Copy)]
+pub struct FilterCoefficients {
+    mode: FilterMode,
+    k: f32,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+}
+
+impl FilterCoefficients {
+    /// Get the coefficients for a note, or `None` if the filter is disabled
+    pub fn new(properties: &FilterProperties, midi_note: f32, sample_rate: f32) -> Option<Self> {
+        if !properties.enabled { return None; }
+
+        // Move the cutoff along with the played note, relative to C4
+        let cutoff = properties.cutoff
+            * 2f32.powf((midi_note - 60.0) / 12.0 * properties.keytrack);
+        let cutoff = cutoff.clamp(10.0, sample_rate * 0.49);
+
+        let g = (consts::PI * cutoff / sample_rate).tan();
+        let k = 2.0 - 1.98 * properties.resonance;
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        Some(Self { mode: properties.mode, k, a1, a2, a3 })
+    }
+}
+
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum FilterMode {
+    #[id = "lowpass"]
+    #[name = "LP"]
+    Lowpass,
+    #[id = "highpass"]
+    #[name = "HP"]
+    Highpass,
+    #[id = "bandpass"]
+    #[name = "BP"]
+    Bandpass,
+    #[id = "notch"]
+    #[name = "Notch"]
+    Notch,
+}
+
+#[derive(Clone, Copy)]
+pub struct FilterProperties {
+    enabled: bool,
+    mode: FilterMode,
+    cutoff: f32,
+    resonance: f32,
+    keytrack: f32,
+}
+
+impl FilterProperties {
+    pub fn new(enabled: bool, mode: FilterMode, cutoff: f32, resonance: f32, keytrack: f32) -> Self {
+        Self {
+            enabled,
+            mode,
+            cutoff,
+            resonance,
+            keytrack,
+        }
+    }
+
+    pub fn modulated(&self, cutoff: f32, resonance: f32) -> Self {
+        Self {
+            cutoff: self.cutoff * 2f32.powf(cutoff * CUTOFF_RANGE),
+            resonance: (self.resonance + resonance).clamp(0.0, 1.0),
+            ..*self
+        }
+    }
+}
+
+impl Default for FilterProperties {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: FilterMode::Lowpass,
+            cutoff: 20_000.0,
+            resonance: 0.0,
+            keytrack: 0.0,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use enum_iterator::Sequence;
+use crate::process::note::{get_wave_sample, WaveKind};
+
+#[derive(Clone, Copy)]
+pub struct LfoProperties {
+    enabled: bool,
+    shape: LfoShape,
+    frequency: f32,
+    depth: f32,
+    phase: f32,
+    pub retrigger: LfoRetrigger,
+}
+
+impl LfoProperties {
+    pub fn new(enabled: bool, shape: LfoShape, frequency: f32, depth: f32, phase: f32,
+               retrigger: LfoRetrigger,
+    ) -> Self {
+        Self {
+            enabled,
+            shape,
+            frequency,
+            depth,
+            phase,
+            retrigger,
+        }
+    }
+}
+
+impl Default for LfoProperties {
+    fn default() -> Self {
+        Self::new(false, LfoShape::Sine, 1.0, 1.0, 0.0, LfoRetrigger::Voice)
+    }
+}
+
+/// The running phase of an LFO
+#[derive(Default, Clone, Copy)]
+pub struct Lfo {
+    phase: f32,
+}
+
+impl Lfo {
+    /// Get the current value and advance the phase by `samples` samples
+    pub fn next(&mut self, properties: &LfoProperties, sample_rate: f32, samples: usize) -> f32 {
+        if !properties.enabled { return 0.0; }
+
+        let value = get_wave_sample(properties.shape.wave_kind(), (self.phase + properties.phase) % 1.0, 0.5);
+        self.phase = (self.phase + properties.frequency * samples as f32 / sample_rate) % 1.0;
+
+        value * properties.depth
+    }
+}
+
+/// The shape of an LFO, these are the oscillator waves that do not need a table, harmonics, noise
+/// or a sample
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum LfoShape {
+    #[id = "sine"]
+    Sine,
+    #[id = "triangle"]
+    Triangle,
+    #[id = "saw"]
+    Saw,
+    #[id = "square"]
+    Square,
+}
+
+impl LfoShape {
+    fn wave_kind(self) -> WaveKind {
+        match self {
+            LfoShape::Sine => WaveKind::Sine,
+            LfoShape::Triangle => WaveKind::Triangle,
+            LfoShape::Saw => WaveKind::Saw,
+            LfoShape::Square => WaveKind::Square,
+        }
+    }
+}
+
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum LfoRetrigger {
+    /// Every voice has its own LFO, which restarts when the note is played
+    #[id = "voice"]
+    Voice,
+    /// A single free-running LFO is shared by all voices
+    #[id = "global"]
+    Global,
+}
+
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum LfoDivision {
+    #[id = "1/1"]
+    #[name = "1/1"]
+    Whole,
+    #[id = "1/2"]
+    #[name = "1/2"]
+    Half,
+    #[id = "1/4"]
+    #[name = "1/4"]
+    Quarter,
+    #[id = "1/4t"]
+    #[name = "1/4T"]
+    QuarterTriplet,
+    #[id = "1/8"]
+    #[name = "1/8"]
+    Eighth,
+    #[id = "1/8t"]
+    #[name = "1/8T"]
+    EighthTriplet,
+    #[id = "1/16"]
+    #[name = "1/16"]
+    Sixteenth,
+    #[id = "1/16t"]
+    #[name = "1/16T"]
+    SixteenthTriplet,
+    #[id = "1/32"]
+    #[name = "1/32"]
+    ThirtySecond,
+}
+
+impl LfoDivision {
+    /// The length of a single cycle in beats
+    pub fn beats(&self) -> f32 {
+        match self {
+            LfoDivision::Whole => 4.0,
+            LfoDivision::Half => 2.0,
+            LfoDivision::Quarter => 1.0,
+            LfoDivision::QuarterTriplet => 2.0 / 3.0,
+            LfoDivision::Eighth => 0.5,
+            LfoDivision::EighthTriplet => 1.0 / 3.0,
+            LfoDivision::Sixteenth => 0.25,
+            LfoDivision::SixteenthTriplet => 1.0 / 6.0,
+            LfoDivision::ThirtySecond => 0.125,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Modulation amounts are bipolar, where 1.0 moves a parameter by the ranges below.
+
+use std::ops::AddAssign;
+use crate::{CC_SOURCE_AMOUNT, ENVELOPE_AMOUNT, LFO_AMOUNT, OSCILLATOR_AMOUNT};
+use crate::params::SynthParams;
+use crate::params::envelope_target::{EnvelopeTargets, ModParameter, ModulationSource, Target};
+
+/// The maximum amount of targets of a single source that are used by the audio thread
+pub const MAX_TARGETS: usize = 32;
+
+/// The pitch range in semitones
+pub const PITCH_RANGE: f32 = 12.0;
+/// The detune range in cents
+pub const DETUNE_RANGE: f32 = 100.0;
+/// The filter cutoff range in octaves
+pub const CUTOFF_RANGE: f32 = 10.0;
+
+/// The summed modulation of an oscillator's parameters for a single note
+#[derive(Default, Clone, Copy)]
+pub struct OscillatorModulation {
+    pub pitch: f32,
+    /// Relative to the oscillator's gain
+    pub volume: f32,
+    pub detune: f32,
+    pub pulse_width: f32,
+}
+
+impl OscillatorModulation {
+    /// The targets that modulate an oscillator, in the order expected by [`Self::from_amounts`]
+    pub fn targets(oscillator_id: usize) -> [Target; 6] {
+        [
+            Target::AllOscillators,
+            Target::Oscillator(oscillator_id),
+            Target::Parameter(ModParameter::OscillatorPitch(oscillator_id).index()),
+            Target::Parameter(ModParameter::OscillatorVolume(oscillator_id).index()),
+            Target::Parameter(ModParameter::OscillatorDetune(oscillator_id).index()),
+            Target::Parameter(ModParameter::OscillatorPulseWidth(oscillator_id).index()),
+        ]
+    }
+
+    /// Combine the modulation amounts of [`Self::targets`], where the oscillator targets
+    /// modulate the volume
+    pub fn from_amounts(amounts: [f32; 6]) -> Self {
+        let [all_oscillators, oscillator, pitch, volume, detune, pulse_width] = amounts;
+        Self {
+            pitch,
+            volume: all_oscillators + oscillator + volume,
+            detune,
+            pulse_width,
+        }
+    }
+}
+
+impl AddAssign<&OscillatorModulation> for OscillatorModulation {
+    fn add_assign(&mut self, rhs: &OscillatorModulation) {
+        self.pitch += rhs.pitch;
+        self.volume += rhs.volume;
+        self.detune += rhs.detune;
+        self.pulse_width += rhs.pulse_width;
+    }
+}
+
+/// A fixed size copy of [`EnvelopeTargets`], which can be used on the audio thread
+#[derive(Clone, Copy)]
+pub struct ModulationTargets {
+    targets: [(Target, f32); MAX_TARGETS],
+    len: usize,
+}
+
+impl ModulationTargets {
+    pub fn new(envelope_targets: &EnvelopeTargets) -> Self {
+        let mut result = Self::default();
+        for (target, amount) in &envelope_targets.targets {
+            if *target == Target::None || result.len == MAX_TARGETS { continue; }
+            result.targets[result.len] = (*target, *amount);
+            result.len += 1;
+        }
+        result
+    }
+
+    pub fn get_amount_for(&self, target: Target) -> f32 {
+        self.targets[..self.len].iter()
+            .filter(|(t, _)| *t == target)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    pub fn get_amounts_for<const N: usize>(&self, targets: [Target; N]) -> [f32; N] {
+        targets.map(|target| self.get_amount_for(target))
+    }
+}
+
+impl Default for ModulationTargets {
+    fn default() -> Self {
+        Self {
+            targets: [(Target::None, 0.0); MAX_TARGETS],
+            len: 0,
+        }
+    }
+}
+
+/// The targets of every modulation source. They are edited in the GUI, which sends a new copy to
+/// the audio thread after every change.
+#[derive(Clone, Copy, Default)]
+pub struct ModulationRouting {
+    pub envelopes: [ModulationTargets; ENVELOPE_AMOUNT],
+    pub lfos: [ModulationTargets; LFO_AMOUNT],
+    pub ccs: [ModulationTargets; CC_SOURCE_AMOUNT],
+    /// The MIDI CC number of every CC source
+    pub cc_numbers: [u8; CC_SOURCE_AMOUNT],
+    pub poly_pressure: ModulationTargets,
+    pub channel_pressure: ModulationTargets,
+    pub timbre: ModulationTargets,
+}
+
+impl ModulationRouting {
+    /// Copy the targets from the parameters, which locks them so this should not be used on the
+    /// audio thread
+    pub fn from_params(params: &SynthParams) -> Self {
+        let targets = |source: ModulationSource| {
+            ModulationTargets::new(&source.targets(params)
+                .lock().expect("Cannot lock modulation targets"))
+        };
+
+        Self {
+            envelopes: std::array::from_fn(|i| targets(ModulationSource::Envelope(i))),
+            lfos: std::array::from_fn(|i| targets(ModulationSource::Lfo(i))),
+            ccs: std::array::from_fn(|i| targets(ModulationSource::Cc(i))),
+            cc_numbers: std::array::from_fn(|i| {
+                (*params.cc_params[i].cc.lock().expect("Cannot lock CC number")).min(127)
+            }),
+            poly_pressure: targets(ModulationSource::PolyPressure),
+            channel_pressure: targets(ModulationSource::ChannelPressure),
+            timbre: targets(ModulationSource::Timbre),
+        }
+    }
+
+    /// How much each envelope shapes the volume of each oscillator, 0 if it does not. Targeting all
+    /// oscillators takes precedence over targeting a single one.
+    pub fn envelope_amounts(&self) -> [[f32; ENVELOPE_AMOUNT]; OSCILLATOR_AMOUNT] {
+        std::array::from_fn(|oscillator| {
+            std::array::from_fn(|envelope| {
+                let targets = &self.envelopes[envelope];
+                let amount = targets.get_amount_for(Target::AllOscillators);
+                if amount > 0.0 {
+                    amount
+                } else {
+                    targets.get_amount_for(Target::Oscillator(oscillator)).max(0.0)
+                }
+            })
+        })
+    }
+
+    /// Sum the modulation of all sources except the envelopes for each of the `targets`
+    pub fn get_modulation<const N: usize>(&self, values: &SourceValues, targets: [Target; N]) -> [f32; N] {
+        let mut modulation = [0.0; N];
+        let sources = self.lfos.iter().zip(values.lfos)
+            .chain(self.ccs.iter().zip(values.ccs))
+            .chain([
+                (&self.poly_pressure, values.poly_pressure),
+                (&self.channel_pressure, values.channel_pressure),
+                (&self.timbre, values.timbre),
+            ]);
+        for (source_targets, value) in sources {
+            if value == 0.0 { continue; }
+
+            for (modulation, target) in modulation.iter_mut().zip(targets) {
+                *modulation += value * source_targets.get_amount_for(target);
+            }
+        }
+        modulation
+    }
+}
+
+/// The current values of the modulation sources of a voice, except for the envelopes which
+/// differ per note
+#[derive(Clone, Copy, Default)]
+pub struct SourceValues {
+    pub lfos: [f32; LFO_AMOUNT],
+    pub ccs: [f32; CC_SOURCE_AMOUNT],
+    pub poly_pressure: f32,
+    pub channel_pressure: f32,
+    pub timbre: f32,
+}
@@ -0,0 +1,53 @@
+use enum_iterator::Sequence;
+
+/// The MPE zone, which decides the master channel and the member channels that are used for the
+/// individual notes
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum MpeZone {
+    /// Master channel 1, with the member channels counting up from channel 2
+    #[id = "lower"]
+    #[name = "Lower"]
+    Lower,
+    /// Master channel 16, with the member channels counting down from channel 15
+    #[id = "upper"]
+    #[name = "Upper"]
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct MpeSettings {
+    pub enabled: bool,
+    pub zone: MpeZone,
+    pub member_channels: u8,
+    /// The pitch bend range of the member channels in semitones
+    pub bend_range: f32,
+}
+
+impl MpeSettings {
+    /// The channel of which the messages apply to all notes in the zone
+    pub fn master_channel(&self) -> u8 {
+        match self.zone {
+            MpeZone::Lower => 0,
+            MpeZone::Upper => 15,
+        }
+    }
+
+    /// Whether the messages on `channel` only apply to the note on that channel
+    pub fn is_member(&self, channel: u8) -> bool {
+        self.enabled && match self.zone {
+            MpeZone::Lower => (1..=self.member_channels).contains(&channel),
+            MpeZone::Upper => (15 - self.member_channels..15).contains(&channel),
+        }
+    }
+}
+
+impl Default for MpeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            zone: MpeZone::Lower,
+            member_channels: 15,
+            bend_range: 48.0,
+        }
+    }
+}
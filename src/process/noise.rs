@@ -0,0 +1,59 @@
+use enum_iterator::Sequence;
+use crate::utils::random::Random;
+
+/// The spectrum of the noise
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum NoiseColor {
+    /// Equal power at every frequency
+    #[id = "white"]
+    #[name = "White"]
+    White,
+    /// 3 dB less power per octave
+    #[id = "pink"]
+    #[name = "Pink"]
+    Pink,
+    /// 6 dB less power per octave
+    #[id = "brown"]
+    #[name = "Brown"]
+    Brown,
+}
+
+/// A noise generator. Every note has its own, seeded by the voice, so the noise of different notes
+/// is not correlated and renders are reproducible.
+#[derive(Clone, Copy)]
+pub struct Noise {
+    random: Random,
+    /// The state of the filters that turn white noise into pink noise
+    pink: [f32; 3],
+    brown: f32,
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            random: Random::new(seed),
+            pink: [0.0; 3],
+            brown: 0.0,
+        }
+    }
+
+    /// Get the next sample, from about -1 to 1
+    pub fn next(&mut self, color: NoiseColor) -> f32 {
+        let white = self.random.next_f32() * 2.0 - 1.0;
+        match color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => {
+                // Paul Kellet's economy filter, which is accurate to within 0.5 dB above 10 Hz
+                self.pink[0] = 0.99765 * self.pink[0] + white * 0.0990460;
+                self.pink[1] = 0.96300 * self.pink[1] + white * 0.2965164;
+                self.pink[2] = 0.57000 * self.pink[2] + white * 1.0526913;
+                (self.pink.iter().sum::<f32>() + white * 0.1848) * 0.25
+            }
+            NoiseColor::Brown => {
+                // Leaky integration of the white noise
+                self.brown = (self.brown + white * 0.02) / 1.02;
+                self.brown * 3.5
+            }
+        }
+    }
+}
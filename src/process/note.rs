@@ -1,121 +1,342 @@
 use std::f32::consts;
-use std::sync::{Arc, Mutex};
 use enum_iterator::Sequence;
 use nih_plug::prelude::Enum;
 use nih_plug::util;
-use nih_plug::util::permit_alloc;
-use crate::ENVELOPE_AMOUNT;
-use crate::params::envelope_target::Target;
-use crate::process::envelope::{Adsr, EnvelopeProperties, Stage};
+use crate::params::envelope_target::{ModParameter, Target};
+use crate::params::oscillator_params::OscillatorParams;
+use crate::process::additive::{get_additive_lanes, get_amplitudes};
+use crate::process::envelope::{Adsr, SegmentPoint, segment_lanes, Stage};
+use crate::process::modulation::{DETUNE_RANGE, OscillatorModulation, PITCH_RANGE};
+use crate::process::noise::{Noise, NoiseColor};
+use crate::process::polyblep::{poly_blamp, poly_blep};
+use crate::process::sample::{Sample, SamplePlayback};
+use crate::process::sub_oscillator::SubBlock;
+use crate::process::unison::Unison;
+use crate::process::voice::VoiceProperties;
+use crate::process::wavetable::Wavetable;
+use crate::utils::f32x4::{F32x4, LANES};
+use crate::utils::random::Random;
+use crate::{ENVELOPE_AMOUNT, MAX_BLOCK_SIZE, MAX_HARMONICS, MAX_UNISON, OSCILLATOR_AMOUNT};
 
 pub struct Note {
-    midi_note: u8,
+    /// The (gliding) note number, which can be in between notes
+    pitch: f32,
     velocity: f32,
     sample_rate: f32,
     oscillator_id: usize,
     time: f32,
-    phase: f32,
+    /// The phase of every unison voice
+    phases: [f32; MAX_UNISON],
+    /// The playback position of every unison voice in frames, which is set from the phases when
+    /// the note starts playing a sample. This has more precision than the phases, which long
+    /// samples need.
+    positions: Option<[f64; MAX_UNISON]>,
+    /// The last sample, which phase modulates the oscillators
+    fm_output: f32,
+    noise: Noise,
 
-    oscillator_properties: Arc<Mutex<OscillatorProperties>>,
-    envelope_properties: Arc<Mutex<[EnvelopeProperties; ENVELOPE_AMOUNT]>>,
     pub stage: Stage,
     pub last_env_gain: f32,
+    /// The gain of every envelope at the last sample
+    env_gains: [f32; ENVELOPE_AMOUNT],
+    /// The gain every envelope started its attack at
+    attack_from: [f32; ENVELOPE_AMOUNT],
 }
 
 impl Note {
-    pub fn new(midi_note: u8, velocity: f32, sample_rate: f32,
-               oscillator_id: usize, oscillator_properties: Arc<Mutex<OscillatorProperties>>,
-               envelope_properties: Arc<Mutex<[EnvelopeProperties; ENVELOPE_AMOUNT]>>) -> Self {
+    pub fn new(midi_note: u8, velocity: f32, sample_rate: f32, oscillator_id: usize,
+               phases: [f32; MAX_UNISON], noise_seed: u32,
+    ) -> Self {
         Self {
-            midi_note,
+            pitch: midi_note as f32,
             velocity,
             sample_rate,
             oscillator_id,
-            oscillator_properties,
-            envelope_properties,
             time: 0.0,
-            phase: 0.0,
+            phases,
+            positions: None,
+            fm_output: 0.0,
+            noise: Noise::new(noise_seed),
             stage: Stage::Held,
             last_env_gain: 0.0,
+            env_gains: [0.0; ENVELOPE_AMOUNT],
+            attack_from: [0.0; ENVELOPE_AMOUNT],
         }
     }
 
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.pitch = pitch;
+    }
+
+    /// Restart the envelopes, keeping the phase so the wave continues without clicking.
+    /// The attacks start at the current gains, so the level does not jump.
+    pub fn retrigger(&mut self, velocity: f32) {
+        self.velocity = velocity;
+        self.attack_from = self.env_gains;
+        self.time = 0.0;
+        self.stage = Stage::Held;
+    }
+
     pub fn release(&mut self) {
-        self.stage = Stage::Released { released_at: self.time, gain_before: self.last_env_gain }
+        self.stage = Stage::Released { released_at: self.time, from: self.env_gains }
     }
 
-    pub fn get_sample(&mut self) -> f32 {
-        let sample = self.get_wave_sample();
+    /// Get the values that stay the same during a block, `modulation` is added to the modulation
+    /// by the envelopes
+    pub fn prepare_block(&self, modulation: &OscillatorModulation, properties: &VoiceProperties,
+    ) -> OscillatorBlock {
+        let osc_properties = &properties.oscillators[self.oscillator_id];
+        let mut modulation = *modulation;
+        modulation += &self.get_oscillator_modulation(properties);
 
-        // Get envelope gain
-        let env_gain =
-        permit_alloc(|| {
-             self.get_envelope_gain()
-        });
+        // Calculate the frequency
+        let frequency =
+            util::f32_midi_note_to_freq(
+                self.pitch
+                    + osc_properties.transpose as f32
+                    + modulation.pitch * PITCH_RANGE
+                    + ((osc_properties.detune + modulation.detune * DETUNE_RANGE) / 100.0)
+            );
 
-        sample * env_gain * self.velocity
+        OscillatorBlock {
+            phase_delta: frequency / self.sample_rate,
+            pulse_width: (osc_properties.pulse_width + modulation.pulse_width).clamp(0.0, 1.0),
+            gain: osc_properties.volume * (1.0 + modulation.volume).max(0.0) * self.velocity,
+        }
     }
 
     pub fn is_finished(&self) -> bool {
         self.stage == Stage::Finished
     }
 
-    fn get_wave_sample(&mut self) -> f32 {
-        // Get the oscillator properties
-        let osc_properties = self.oscillator_properties.lock()
-            .expect("Failed to acquire oscillator_properties lock");
+    fn get_oscillator_modulation(&self, properties: &VoiceProperties) -> OscillatorModulation {
+        let id = self.oscillator_id;
+        let [pitch, volume, detune, pulse_width] = self.get_parameter_modulation([
+            ModParameter::OscillatorPitch(id),
+            ModParameter::OscillatorVolume(id),
+            ModParameter::OscillatorDetune(id),
+            ModParameter::OscillatorPulseWidth(id),
+        ], properties);
 
-        if !osc_properties.enabled { return 0.0; }
+        OscillatorModulation { pitch, volume, detune, pulse_width }
+    }
 
+    /// Get the summed envelope modulation for each of the `parameters`
+    pub fn get_parameter_modulation<const N: usize>(&self, parameters: [ModParameter; N],
+                                                    properties: &VoiceProperties,
+    ) -> [f32; N] {
+        let targets = parameters.map(|parameter| Target::Parameter(parameter.index()));
 
-        // Calculate the frequency
-        let frequency =
-            util::f32_midi_note_to_freq(
-                self.midi_note as f32
-                    + osc_properties.transpose as f32
-                    + (osc_properties.detune / 100.0)
-            );
+        let mut modulation = [0.0; N];
+        let envelopes = properties.envelopes.iter().zip(&properties.routing.envelopes);
+        for (i, (adsr, envelope_targets)) in envelopes.enumerate() {
+            let amounts = envelope_targets.get_amounts_for(targets);
+            if amounts.iter().all(|amount| *amount == 0.0) { continue; }
 
-        // Get wave value
-        let sample = get_wave_sample(osc_properties.kind, self.phase, osc_properties.pulse_width);
+            let value = get_env_point(*adsr, &self.stage, self.time, i, self.attack_from[i])
+                .map_or(0.0, |point| point.gain());
+            for (modulation, amount) in modulation.iter_mut().zip(amounts) {
+                *modulation += value * amount;
+            }
+        }
+        modulation
+    }
+}
 
-        // Update phase
-        self.phase += frequency / self.sample_rate;
-        self.phase %= 1.0;
+/// The values of a note that stay the same during a block
+#[derive(Clone, Copy, Default)]
+pub struct OscillatorBlock {
+    phase_delta: f32,
+    pulse_width: f32,
+    /// The oscillator volume, including the modulation and velocity
+    gain: f32,
+}
 
-        sample * osc_properties.volume
+/// Render a block of up to [`LANES`] voices, of which the notes of all oscillators are processed
+/// together. The stereo samples of every voice are added to its lane of `output`.
+///
+/// The oscillators are rendered in order for every sample, so an oscillator is phase modulated by
+/// the current sample of the oscillators before it, and by the previous sample of itself and the
+/// oscillators after it.
+pub fn render_notes(mut notes: [Option<&mut [Note; OSCILLATOR_AMOUNT]>; LANES],
+                    blocks: &[[OscillatorBlock; OSCILLATOR_AMOUNT]; LANES],
+                    properties: &VoiceProperties,
+                    wavetables: &[Option<Wavetable>; OSCILLATOR_AMOUNT],
+                    samples: &[Option<Sample>; OSCILLATOR_AMOUNT],
+                    sub: &mut SubBlock, output: &mut [[F32x4; 2]],
+) {
+    let mut envelopes = [[F32x4::ZERO; MAX_BLOCK_SIZE]; OSCILLATOR_AMOUNT];
+    for (i, envelopes) in envelopes.iter_mut().enumerate() {
+        for envelope in &mut envelopes[..output.len()] {
+            *envelope = next_envelope_gains(&mut notes, i, properties);
+        }
     }
 
-    fn get_envelope_gain(&mut self) -> f32 {
-        let mut gain = 1.0;
-        let envs = self.get_envelopes();
-        for (adsr, amount) in envs {
-            let env_gain = get_env_gain(adsr, &self.stage, self.time);
-            // Update stage TODO only if all finished
-            if env_gain.finished { self.stage = Stage::Finished; }
-            self.last_env_gain = env_gain.gain;
-            gain *= env_gain.gain / amount
-        }
-        // Update time
-        self.time += 1.0 / self.sample_rate;
-        gain
-    }
-
-    fn get_envelopes(&self) -> Vec<(Adsr, f32)> {
-        let envelope_properties = self.envelope_properties.lock()
-            .expect("Failed to acquire envelope_properties lock");
-        envelope_properties.iter().filter_map(|envelope| {
-            let mut amount = envelope.get_amount_for(Target::AllOscillators);
-            if amount <= 0.0 {
-                amount = envelope.get_amount_for(Target::Oscillator(self.oscillator_id));
-                if amount <= 0.0 { return None; }
+    let phase_delta: [F32x4; OSCILLATOR_AMOUNT] =
+        std::array::from_fn(|i| F32x4::from_fn(|lane| blocks[lane][i].phase_delta));
+    let pulse_width: [F32x4; OSCILLATOR_AMOUNT] =
+        std::array::from_fn(|i| F32x4::from_fn(|lane| blocks[lane][i].pulse_width));
+    let gain: [F32x4; OSCILLATOR_AMOUNT] =
+        std::array::from_fn(|i| F32x4::from_fn(|lane| blocks[lane][i].gain));
+
+    let mut phases: [[F32x4; MAX_UNISON]; OSCILLATOR_AMOUNT] = std::array::from_fn(|i| {
+        std::array::from_fn(|voice| F32x4::from_fn(|lane| {
+            notes[lane].as_ref().map_or(0.0, |notes| notes[i].phases[voice])
+        }))
+    });
+    let mut fm_outputs: [F32x4; OSCILLATOR_AMOUNT] = std::array::from_fn(|i| {
+        F32x4::from_fn(|lane| notes[lane].as_ref().map_or(0.0, |notes| notes[i].fm_output))
+    });
+    let mut noises: [[Noise; LANES]; OSCILLATOR_AMOUNT] = std::array::from_fn(|i| {
+        std::array::from_fn(|lane| notes[lane].as_ref().map_or(Noise::new(0), |notes| notes[i].noise))
+    });
+    // Samples are played back by moving the position through the frames instead of the phase
+    let playing_samples: [Option<&Sample>; OSCILLATOR_AMOUNT] = std::array::from_fn(|i| {
+        samples[i].as_ref().filter(|_| properties.oscillators[i].kind == WaveKind::Sample)
+    });
+    let mut positions: [[[f64; LANES]; MAX_UNISON]; OSCILLATOR_AMOUNT] = std::array::from_fn(|i| {
+        std::array::from_fn(|voice| std::array::from_fn(|lane| {
+            let (Some(notes), Some(sample)) = (&notes[lane], playing_samples[i]) else { return 0.0; };
+            let note = &notes[i];
+            note.positions.map_or_else(|| sample.frame_at(note.phases[voice]), |positions| positions[voice])
+        }))
+    });
+    let frames_per_cycle: [f64; OSCILLATOR_AMOUNT] = std::array::from_fn(|i| {
+        playing_samples[i].map_or(0.0, |sample| {
+            sample.frames_per_cycle(properties.oscillators[i].sample.root_key)
+        })
+    });
+    // The current wave of every oscillator, which is the carrier for ring and amplitude modulation
+    let mut waves = [F32x4::ZERO; OSCILLATOR_AMOUNT];
+    // How many samples ago the first unison voice of every oscillator wrapped around during the
+    // current sample, or -1 if it did not, which is used for hard sync
+    let mut wraps = [F32x4::splat(-1.0); OSCILLATOR_AMOUNT];
+
+    for (sample, [left, right]) in output.iter_mut().enumerate() {
+        for (i, osc_properties) in properties.oscillators.iter().enumerate() {
+            if !osc_properties.enabled {
+                fm_outputs[i] = F32x4::ZERO;
+                waves[i] = F32x4::ZERO;
+                wraps[i] = F32x4::splat(-1.0);
+                continue;
+            }
+            // The previous oscillator is the carrier or the master
+            let previous = i.checked_sub(1);
+
+            // The phase offset caused by the FM, in cycles
+            let mut modulation = F32x4::ZERO;
+            for (index, fm_output) in osc_properties.fm.iter().zip(&fm_outputs) {
+                if *index != 0.0 {
+                    modulation += *fm_output * (index / consts::TAU);
+                }
+            }
+
+            // Noise does not depend on the phase, so it is the same for every unison voice
+            let noise = if osc_properties.kind == WaveKind::Noise {
+                F32x4::from_fn(|lane| noises[i][lane].next(osc_properties.noise_color))
+            } else {
+                F32x4::ZERO
+            };
+
+            let sample = playing_samples[i];
+
+            let unison = &osc_properties.unison;
+            let mut mono = F32x4::ZERO;
+            let mut stereo = [F32x4::ZERO; 2];
+            for (voice, phase) in phases[i][..unison.voices].iter_mut().enumerate() {
+                let phase_delta = phase_delta[i] * unison.ratios[voice];
+                let position = &mut positions[i][voice];
+                let wave = match (osc_properties.kind, sample) {
+                    (WaveKind::Noise, _) => noise,
+                    (WaveKind::Sample, Some(sample)) => F32x4::from_fn(|lane| {
+                        sample.sample(position[lane], &osc_properties.sample)
+                    }),
+                    _ => osc_properties.get_wave_lanes(
+                        (*phase + modulation).fract(), phase_delta, pulse_width[i], wavetables[i].as_ref(),
+                    ),
+                };
+                let wave = match (osc_properties.combine, previous) {
+                    (CombineMode::Ring, Some(carrier)) => waves[carrier] * wave,
+                    (CombineMode::Am, Some(carrier)) => waves[carrier] * (wave * 0.5 + 0.5),
+                    _ => wave,
+                };
+                mono += wave;
+                stereo[0] += wave * unison.gains[voice][0];
+                stereo[1] += wave * unison.gains[voice][1];
+
+                if let Some(sample) = sample {
+                    // Update the playback position
+                    for (position, phase_delta) in position.iter_mut().zip(phase_delta.0) {
+                        let step = phase_delta as f64 * frames_per_cycle[i];
+                        *position = sample.advance(*position, step, &osc_properties.sample);
+                    }
+                    continue;
+                }
+
+                // Update phase
+                let next_phase = *phase + phase_delta;
+                if voice == 0 {
+                    wraps[i] = F32x4::from_fn(|lane| {
+                        if next_phase.0[lane] >= 1.0 {
+                            (next_phase.0[lane] - 1.0) / phase_delta.0[lane]
+                        } else {
+                            -1.0
+                        }
+                    });
+                }
+                *phase = next_phase.fract();
+
+                // Restart the phase when the master wrapped around, at the same point in time
+                if let (CombineMode::Sync, Some(master)) = (osc_properties.combine, previous) {
+                    let wrap = wraps[master];
+                    *phase = F32x4::from_fn(|lane| {
+                        if wrap.0[lane] >= 0.0 {
+                            (wrap.0[lane] * phase_delta.0[lane]).fract()
+                        } else {
+                            phase.0[lane]
+                        }
+                    });
+                }
+            }
+
+            let mono = mono * (unison.voices as f32).recip();
+            waves[i] = mono;
+            let gain = gain[i] * envelopes[i][sample];
+            fm_outputs[i] = mono * gain;
+            if osc_properties.output {
+                *left += stereo[0] * gain;
+                *right += stereo[1] * gain;
+            }
+        }
+
+        // The sub-oscillator follows the envelope of the first oscillator
+        if properties.sub.enabled {
+            let sub_sample = sub.next(&properties.sub) * envelopes[0][sample];
+            *left += sub_sample;
+            *right += sub_sample;
+        }
+    }
+
+    for (lane, notes) in notes.iter_mut().enumerate() {
+        let Some(notes) = notes else { continue; };
+        for (i, note) in notes.iter_mut().enumerate() {
+            for (phase, lanes) in note.phases.iter_mut().zip(&phases[i]) {
+                *phase = lanes.0[lane];
+            }
+            if playing_samples[i].is_some() {
+                note.positions = Some(positions[i].map(|lanes| lanes[lane]));
             }
-            Some((envelope.adsr, amount))
-        }).collect()
+            note.fm_output = fm_outputs[i].0[lane];
+            note.noise = noises[i][lane];
+        }
     }
 }
 
-fn get_wave_sample(wave: WaveKind, phase: f32, pulse_width: f32) -> f32 {
+pub fn get_wave_sample(wave: WaveKind, phase: f32, pulse_width: f32) -> f32 {
     match wave {
         WaveKind::Sine => {
             (phase * consts::TAU).sin()
@@ -129,52 +350,108 @@ fn get_wave_sample(wave: WaveKind, phase: f32, pulse_width: f32) -> f32 {
         WaveKind::Square => {
             if phase < pulse_width { 1.0 } else { -1.0 }
         }
+        // Rendered from the oscillator's table, harmonics, noise generator or sample by the caller
+        WaveKind::Wavetable | WaveKind::Additive | WaveKind::Noise | WaveKind::Sample => 0.0,
     }
 }
 
-fn get_env_gain(adsr: Adsr, stage: &Stage, time: f32) -> Gain {
-    match stage {
-        Stage::Held => {
-            if time < adsr.attack() {
-                // Attack phase
-                Gain::new(time / adsr.attack())
-            } else if time < adsr.attack() + adsr.decay() {
-                // Attack -> sustain phase
-                Gain::new(
-                    1.0 - (time - adsr.attack()) / adsr.decay() * (1.0 - adsr.sustain())
-                )
-            } else {
-                Gain::new(adsr.sustain())
-            }
+/// Same as [`get_wave_sample`], for every lane
+pub fn get_wave_lanes(wave: WaveKind, phase: F32x4, pulse_width: F32x4) -> F32x4 {
+    match wave {
+        WaveKind::Sine => (phase * consts::TAU).map(f32::sin),
+        WaveKind::Triangle => ((phase - (phase + 0.5).floor()) * 2.0).abs() * 2.0 - 1.0,
+        WaveKind::Saw => (phase - (phase + 0.5).floor()) * 2.0,
+        WaveKind::Square => F32x4::from_fn(|lane| {
+            if phase.0[lane] < pulse_width.0[lane] { 1.0 } else { -1.0 }
+        }),
+        WaveKind::Wavetable | WaveKind::Additive | WaveKind::Noise | WaveKind::Sample => F32x4::ZERO,
+    }
+}
+
+/// Same as [`get_wave_lanes`], but smooths out the discontinuities using PolyBLEP (saw, square)
+/// and PolyBLAMP (triangle) to reduce aliasing.
+pub fn get_bandlimited_wave_lanes(wave: WaveKind, phase: F32x4, phase_delta: F32x4,
+                                  pulse_width: F32x4,
+) -> F32x4 {
+    // The corrections only work when a discontinuity affects at most one sample on each side
+    let dt = phase_delta.min(F32x4::splat(0.5));
+    let sample = get_wave_lanes(wave, phase, pulse_width);
+    let blep = |t: F32x4| F32x4::from_fn(|lane| poly_blep(t.0[lane], dt.0[lane]));
+    let blamp = |t: F32x4| F32x4::from_fn(|lane| poly_blamp(t.0[lane], dt.0[lane]));
+
+    match wave {
+        WaveKind::Sine | WaveKind::Wavetable | WaveKind::Additive | WaveKind::Noise
+        | WaveKind::Sample => sample,
+        WaveKind::Triangle => {
+            // The slope changes from -4 to 4 at phase 0 and back at phase 0.5
+            sample + dt * 4.0 * (blamp(phase) - blamp((phase + 0.5).fract()))
         }
-        Stage::Released { released_at, gain_before: volume_before } => {
-            if time <= released_at + adsr.release() {
-                Gain::new(
-                    volume_before - (time - released_at) /
-                        (adsr.release() / volume_before)
-                )
-            } else {
-                Gain::finished()
-            }
+        WaveKind::Saw => {
+            // The saw drops from 1 to -1 at phase 0.5
+            sample - blep((phase + 0.5).fract())
         }
-        Stage::Finished => {
-            Gain::finished()
+        WaveKind::Square => {
+            // Rising edge at phase 0, falling edge at the pulse width
+            sample + blep(phase) - blep((phase - pulse_width + 1.0).fract())
         }
     }
 }
 
-struct Gain {
-    gain: f32,
-    finished: bool,
-}
+/// Get the envelope gain of oscillator `index` for every lane, and advance the time of its notes
+/// by one sample. Every note can be in a different envelope stage, but the curves of all lanes are
+/// calculated together.
+fn next_envelope_gains(notes: &mut [Option<&mut [Note; OSCILLATOR_AMOUNT]>; LANES], index: usize,
+                       properties: &VoiceProperties,
+) -> F32x4 {
+    let mut gain = F32x4::splat(1.0);
+    let amounts = properties.envelope_amounts[index];
+    for (envelope, (adsr, amount)) in properties.envelopes.iter().zip(amounts).enumerate() {
+        let mut finished = [false; LANES];
+        let points = std::array::from_fn(|lane| {
+            let Some(notes) = &notes[lane] else { return SegmentPoint::default(); };
+            let note = &notes[index];
+            let point = get_env_point(*adsr, &note.stage, note.time, envelope, note.attack_from[envelope]);
+            finished[lane] = point.is_none();
+            point.unwrap_or_default()
+        });
+        let env_gain = segment_lanes(&points);
+
+        for (lane, notes) in notes.iter_mut().enumerate() {
+            let Some(notes) = notes else { continue; };
+            let note = &mut notes[index];
+            // Also kept for the envelopes that only modulate parameters, so a retrigger can start
+            // their attacks at this gain
+            note.env_gains[envelope] = env_gain.0[lane];
+            if amount <= 0.0 { continue; }
 
-impl Gain {
-    pub fn new(gain: f32) -> Self {
-        Self { gain, finished: false }
+            // Update stage TODO only if all finished
+            if finished[lane] { note.stage = Stage::Finished; }
+            note.last_env_gain = env_gain.0[lane];
+        }
+        if amount > 0.0 {
+            gain = gain * env_gain / amount;
+        }
     }
 
-    pub fn finished() -> Self {
-        Self { gain: 0.0, finished: true }
+    // Update time
+    for notes in notes.iter_mut().flatten() {
+        let note = &mut notes[index];
+        note.time += 1.0 / note.sample_rate;
+    }
+    gain
+}
+
+/// Get the point of the envelope with `index`, `attack_from` is where its attack started.
+/// Returns `None` when the envelope has finished.
+fn get_env_point(adsr: Adsr, stage: &Stage, time: f32, index: usize, attack_from: f32,
+) -> Option<SegmentPoint> {
+    match stage {
+        Stage::Held => Some(adsr.held_point(attack_from, time)),
+        Stage::Released { released_at, from } => {
+            (time <= released_at + adsr.release())
+                .then(|| adsr.released_point(from[index], time - released_at))
+        }
+        Stage::Finished => None,
     }
 }
 
@@ -188,29 +465,146 @@ pub enum WaveKind {
     Saw,
     #[id = "square"]
     Square,
+    #[id = "wavetable"]
+    #[name = "Table"]
+    Wavetable,
+    #[id = "additive"]
+    Additive,
+    #[id = "noise"]
+    Noise,
+    #[id = "sample"]
+    Sample,
 }
 
-#[derive(Clone)]
+/// How an oscillator is combined with the previous oscillator
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum CombineMode {
+    /// The oscillators are summed
+    #[id = "mix"]
+    #[name = "Mix"]
+    Mix,
+    /// The previous oscillator is multiplied by this one
+    #[id = "ring"]
+    #[name = "Ring"]
+    Ring,
+    /// The volume of the previous oscillator follows this one
+    #[id = "am"]
+    #[name = "AM"]
+    Am,
+    /// The phase is restarted every time the previous oscillator starts a new cycle
+    #[id = "sync"]
+    #[name = "Sync"]
+    Sync,
+}
+
+#[derive(Clone, Copy)]
 pub struct OscillatorProperties {
     kind: WaveKind,
     pulse_width: f32,
     volume: f32,
     enabled: bool,
+    /// Whether the oscillator is heard, or only modulates other oscillators
+    output: bool,
+    /// How this oscillator is combined with the previous one
+    combine: CombineMode,
+    noise_color: NoiseColor,
     transpose: i32,
     detune: f32,
+    /// The unison voices, which also include the pan of the oscillator
+    unison: Unison,
+    /// Start the unison voices at a random phase instead of at 0, a single voice always starts
+    /// at 0 so the attack of a note stays the same
+    random_phase: bool,
+    antialias: bool,
+    wavetable_position: f32,
+    sample: SamplePlayback,
+    /// The amplitude of every harmonic, with the balance and tilt applied
+    additive: [f32; MAX_HARMONICS],
+    /// How much every oscillator modulates the phase of this one
+    fm: [f32; OSCILLATOR_AMOUNT],
 }
 
 impl OscillatorProperties {
-    pub fn new(kind: WaveKind, pulse_width: f32, volume: f32, enabled: bool,
-               transpose: i32, detune: f32,
-    ) -> Self {
+    /// Get the properties from the oscillator parameters, advancing the smoothers by `steps`.
+    /// `harmonics` are the amplitudes that are used by the additive wave.
+    pub fn from_params(params: &OscillatorParams, harmonics: &[f32; MAX_HARMONICS], steps: u32) -> Self {
+        let kind = params.wave_kind.value();
+        // Every unison voice would play the same noise
+        let unison_voices = if kind == WaveKind::Noise {
+            1
+        } else {
+            params.unison_voices.value() as usize
+        };
+        let additive = if kind == WaveKind::Additive {
+            get_amplitudes(
+                harmonics,
+                params.additive_balance.smoothed.next_step(steps),
+                params.additive_tilt.smoothed.next_step(steps),
+            )
+        } else {
+            [0.0; MAX_HARMONICS]
+        };
+
         Self {
             kind,
-            pulse_width,
-            volume,
-            enabled,
-            transpose,
-            detune,
+            pulse_width: params.pulse_width.smoothed.next_step(steps),
+            volume: util::db_to_gain_fast(params.volume.smoothed.next_step(steps)),
+            enabled: params.enabled.value(),
+            output: params.output.value(),
+            combine: params.combine.value(),
+            noise_color: params.noise_color.value(),
+            transpose: params.transpose.value(),
+            detune: params.detune.value(),
+            unison: Unison::new(
+                unison_voices,
+                params.unison_detune.value(),
+                params.unison_curve.value(),
+                params.unison_width.smoothed.next_step(steps),
+                params.unison_blend.smoothed.next_step(steps),
+                params.pan.smoothed.next_step(steps),
+            ),
+            random_phase: params.unison_random_phase.value(),
+            antialias: params.antialias.value(),
+            wavetable_position: params.wavetable_position.smoothed.next_step(steps),
+            sample: SamplePlayback::new(
+                params.sample.root_key.value() as f32,
+                params.sample.start.value(),
+                params.sample.loop_start.value(),
+                params.sample.loop_end.value(),
+                params.sample.crossfade.value(),
+                params.sample.mode.value(),
+            ),
+            additive,
+            fm: std::array::from_fn(|j| params.fm[j].index.smoothed.next_step(steps)),
+        }
+    }
+
+    /// Get the wave of this oscillator for every lane
+    fn get_wave_lanes(&self, phase: F32x4, phase_delta: F32x4, pulse_width: F32x4,
+                      wavetable: Option<&Wavetable>,
+    ) -> F32x4 {
+        if self.kind == WaveKind::Wavetable {
+            F32x4::from_fn(|lane| wavetable.map_or(0.0, |table| {
+                table.sample(self.wavetable_position, phase.0[lane], phase_delta.0[lane])
+            }))
+        } else if self.kind == WaveKind::Additive {
+            get_additive_lanes(phase, phase_delta, &self.additive)
+        } else if self.antialias {
+            get_bandlimited_wave_lanes(self.kind, phase, phase_delta, pulse_width)
+        } else {
+            get_wave_lanes(self.kind, phase, pulse_width)
+        }
+    }
+
+    /// The starting phase of every unison voice of a new note, or the playback position for
+    /// samples
+    pub fn initial_phases(&self, random: &mut Random) -> [f32; MAX_UNISON] {
+        if self.kind == WaveKind::Sample {
+            [self.sample.start; MAX_UNISON]
+        } else if self.random_phase && self.unison.voices > 1 {
+            std::array::from_fn(|_| random.next_f32())
+        } else {
+            [0.0; MAX_UNISON]
         }
     }
 }
@@ -222,8 +616,18 @@ impl Default for OscillatorProperties {
             pulse_width: 0.5,
             volume: 1.0,
             enabled: true,
+            output: true,
+            combine: CombineMode::Mix,
+            noise_color: NoiseColor::White,
             transpose: 0,
             detune: 0.0,
+            unison: Unison::default(),
+            random_phase: false,
+            antialias: true,
+            wavetable_position: 0.0,
+            sample: SamplePlayback::default(),
+            additive: [0.0; MAX_HARMONICS],
+            fm: [0.0; OSCILLATOR_AMOUNT],
         }
     }
 }
\ No newline at end of file
@@ -1,125 +1,691 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use nih_plug::prelude::*;
-use nih_plug::util::permit_alloc;
 use crate::utils::fixed_map::FixedMap;
-use crate::{ENVELOPE_AMOUNT, OSCILLATOR_AMOUNT, Synth};
+use crate::utils::random::Random;
+use crate::{ENVELOPE_AMOUNT, LFO_AMOUNT, MAX_POLYPHONY, MIDI_CHANNELS, OSCILLATOR_AMOUNT, Synth};
 use crate::params::SynthParams;
-use crate::process::envelope::{Adsr, EnvelopeProperties};
-use crate::process::note::{Note, OscillatorProperties};
-use crate::utils::{get_envelope_array, get_oscillator_array};
+use crate::params::envelope_target::ModParameter;
+use crate::process::additive::Harmonics;
+use crate::process::envelope::Adsr;
+use crate::process::filter::FilterProperties;
+use crate::process::lfo::{Lfo, LfoProperties};
+use crate::process::modulation::{DETUNE_RANGE, ModulationRouting};
+use crate::process::mpe::MpeSettings;
+use crate::process::note::OscillatorProperties;
+use crate::process::sample::{Sample, Samples};
+use crate::process::sub_oscillator::SubProperties;
+use crate::process::voice::{Glide, NotePriority, render_voices, Voice, VoiceMode, VoiceProperties, VoiceStealing};
+use crate::process::wavetable::{Wavetable, Wavetables};
+use crate::utils::f32x4::LANES;
+use crate::utils::swap_slot::SwapSlot;
+
+/// The MIDI CC numbers of the pedals
+const SUSTAIN_PEDAL: u8 = 64;
+const SOSTENUTO_PEDAL: u8 = 66;
+/// The MIDI CC that MPE controllers use for the timbre of a note
+const TIMBRE_CC: u8 = 74;
+
+/// The maximum amount of keys that can be held, with MPE every channel can hold every note
+const MAX_KEYS: usize = MIDI_CHANNELS * 128;
+
+/// The seed of the random values of the notes, like the starting phases and the noise
+const RANDOM_SEED: u32 = 1;
+
+/// The MIDI channel and note number of a voice
+type VoiceKey = (u8, u8);
+
+/// A key that is held in one of the monophonic voice modes, with the voice ID and channel of its
+/// note so the voice can return to it
+#[derive(Clone, Copy)]
+struct HeldKey {
+    key: VoiceKey,
+    voice_id: i32,
+    channel: u8,
+    velocity: f32,
+}
+
+impl HeldKey {
+    fn note(&self) -> u8 {
+        self.key.1
+    }
+}
+
+/// Whether the sustain and sostenuto pedals are pressed
+#[derive(Clone, Copy, Default)]
+pub struct Pedals {
+    pub sustain: bool,
+    pub sostenuto: bool,
+}
 
 pub struct NoteStorage {
-    notes: FixedMap<u8, [Note; OSCILLATOR_AMOUNT]>,
-    released_notes: Vec<Note>,
+    voices: FixedMap<VoiceKey, Voice>,
+    released_voices: Vec<Voice>,
+    /// The voice ID, channel and note of the voices that ended, which still need to be reported to
+    /// the host
+    terminated_voices: Vec<(i32, u8, u8)>,
+    /// The voice used by the monophonic voice modes
+    mono_voice: Option<Voice>,
+    /// The currently held keys, in the order they were pressed
+    held_keys: Vec<HeldKey>,
+
+    pedals: Pedals,
+    /// The keys that were released while a pedal kept them playing
+    sustained_keys: Vec<VoiceKey>,
+    /// The keys that were held when the sostenuto pedal was pressed
+    sostenuto_keys: Vec<VoiceKey>,
+
+    /// The pitch bend of each MIDI channel, from -1 to 1
+    pitch_bend: [Smoother<f32>; MIDI_CHANNELS],
+    /// The pitch bend range in semitones
+    bend_up: f32,
+    bend_down: f32,
+    mpe: MpeSettings,
 
-    oscillator_properties: [Arc<Mutex<OscillatorProperties>>; OSCILLATOR_AMOUNT],
-    envelope_properties: Arc<Mutex<[EnvelopeProperties; ENVELOPE_AMOUNT]>>,
+    /// The latest value of every MIDI CC, from 0 to 1
+    cc_values: [f32; 128],
 
-    oscillator_range: [usize; OSCILLATOR_AMOUNT],
+    voice_properties: VoiceProperties,
+    /// The wavetables of the oscillators, received from [`Wavetables`]
+    wavetables: [Option<Wavetable>; OSCILLATOR_AMOUNT],
+    /// The samples of the oscillators, received from [`Samples`]
+    samples: [Option<Sample>; OSCILLATOR_AMOUNT],
+    /// The harmonics of the additive oscillators, received from the GUI
+    harmonics: Harmonics,
+    global_lfos: [Lfo; LFO_AMOUNT],
+    voice_stealing: VoiceStealing,
+    voice_mode: VoiceMode,
+    note_priority: NotePriority,
+    glide: Glide,
+    /// Used for the random phases of new notes
+    random: Random,
+
+    sample_rate: f32,
 }
 
 impl NoteStorage {
     pub fn new() -> Self {
-        let oscillator_range = get_oscillator_array();
-        let envelope_range = get_envelope_array();
-
         Self {
-            notes: FixedMap::new(64),
-            released_notes: Vec::with_capacity(64 * OSCILLATOR_AMOUNT),
-            oscillator_properties: oscillator_range.map(|_| Arc::new(Mutex::new(OscillatorProperties::default()))),
-            envelope_properties: Arc::new(Mutex::new(envelope_range.map(|_| EnvelopeProperties::default()))),
-            oscillator_range,
+            voices: FixedMap::new(MAX_POLYPHONY),
+            // Stolen voices are released as well, so there can be twice as many
+            released_voices: Vec::with_capacity(MAX_POLYPHONY * 2),
+            terminated_voices: Vec::with_capacity(MAX_POLYPHONY * 3),
+            mono_voice: None,
+            held_keys: Vec::with_capacity(MAX_KEYS),
+            pedals: Pedals::default(),
+            sustained_keys: Vec::with_capacity(MAX_KEYS),
+            sostenuto_keys: Vec::with_capacity(MAX_KEYS),
+            pitch_bend: std::array::from_fn(|_| Smoother::new(SmoothingStyle::Linear(10.0))),
+            bend_up: 2.0,
+            bend_down: 2.0,
+            mpe: MpeSettings::default(),
+            cc_values: [0.0; 128],
+            voice_properties: VoiceProperties::default(),
+            wavetables: std::array::from_fn(|_| None),
+            samples: std::array::from_fn(|_| None),
+            harmonics: Harmonics::default(),
+            global_lfos: [Lfo::default(); LFO_AMOUNT],
+            voice_stealing: VoiceStealing::Oldest,
+            voice_mode: VoiceMode::Poly,
+            note_priority: NotePriority::Last,
+            glide: Glide::default(),
+            random: Random::new(RANDOM_SEED),
+            sample_rate: 1.0,
         }
     }
 
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Restart the random values from the seed, so renders that start here are reproducible
+    pub fn reset_random(&mut self) {
+        self.random = Random::new(RANDOM_SEED);
+    }
+
+    pub fn pedals(&self) -> Pedals {
+        self.pedals
+    }
+
 
     pub fn process_midi(&mut self,
                         event: PluginNoteEvent<Synth>,
+                        params: &SynthParams,
                         sample_rate: f32,
     ) {
         match event {
-            NoteEvent::NoteOn { note, velocity, .. } => {
-                // Create new waves (for each oscillator) for this note
-                let new_notes =
-                    self.oscillator_range.map(|i| {
-                        Note::new(note, velocity, sample_rate, i,
-                                  Arc::clone(&self.oscillator_properties[i]),
-                                  Arc::clone(&self.envelope_properties))
-                    });
-
-                // Add new notes to map
-                let old_notes: Option<[Note; OSCILLATOR_AMOUNT]> = self.notes.insert(note, new_notes);
+            NoteEvent::NoteOn { channel, note, .. } => {
+                // The key is held again, so it is no longer kept playing by a pedal
+                let key = self.voice_key(channel, note);
+                self.sustained_keys.retain(|sustained| *sustained != key);
+            }
+            NoteEvent::MidiCC { cc, value, .. } => {
+                self.cc_values[cc as usize] = value;
+            }
+            _ => (),
+        }
+
+        match event {
+            NoteEvent::NoteOff { channel, note, .. } if self.is_sustained(self.voice_key(channel, note)) => {
+                let key = self.voice_key(channel, note);
+                if !self.sustained_keys.contains(&key) {
+                    self.sustained_keys.push(key);
+                }
+            }
+            NoteEvent::NoteOn { voice_id, note, channel, velocity, .. } if self.voice_mode != VoiceMode::Poly => {
+                let voice_id = voice_id.unwrap_or_else(|| compute_fallback_voice_id(note, channel));
+                self.play_mono(self.voice_key(channel, note), voice_id, channel, velocity, sample_rate);
+            }
+            NoteEvent::NoteOff { channel, note, .. } => {
+                self.release_key(self.voice_key(channel, note));
+            }
+            NoteEvent::NoteOn { voice_id, note, channel, velocity, .. } => {
+                let key = self.voice_key(channel, note);
+                let voice_id = voice_id.unwrap_or_else(|| compute_fallback_voice_id(note, channel));
+                let new_voice = Voice::new(voice_id, note, channel, velocity, sample_rate,
+                                           &self.voice_properties, &mut self.random);
+
                 // If a note was already playing, release it and save to the list
-                if let Some(old_notes) = old_notes {
-                    self.release_note(old_notes);
+                if let Some(old_voice) = self.voices.remove(&key) {
+                    self.release_voice(old_voice);
                 }
+                // Make room for the new voice if the polyphony limit is reached
+                while self.voices.is_full() {
+                    self.steal_voice();
+                }
+                // Add new voice to map
+                self.voices.insert(key, new_voice);
+            }
+            NoteEvent::MidiCC { cc: SUSTAIN_PEDAL, value, .. } => {
+                self.pedals.sustain = value >= 0.5;
+                self.release_sustained_keys();
             }
-            NoteEvent::NoteOff { note, .. } => {
-                let notes = self.notes.remove(&note);
-                if let Some(notes) = notes {
-                    self.release_note(notes);
+            NoteEvent::MidiCC { cc: SOSTENUTO_PEDAL, value, .. } => {
+                let pressed = value >= 0.5;
+                if pressed && !self.pedals.sostenuto {
+                    // Latch the keys that are currently held down
+                    self.sostenuto_keys.clear();
+                    let sustained_keys = &self.sustained_keys;
+                    match self.voice_mode {
+                        VoiceMode::Poly => self.sostenuto_keys.extend(
+                            self.voices.map.keys().filter(|key| !sustained_keys.contains(key))
+                        ),
+                        _ => self.sostenuto_keys.extend(
+                            self.held_keys.iter().map(|held| held.key)
+                                .filter(|key| !sustained_keys.contains(key))
+                        ),
+                    }
+                }
+                self.pedals.sostenuto = pressed;
+                if !pressed {
+                    self.release_sustained_keys();
+                    self.sostenuto_keys.clear();
+                }
+            }
+            NoteEvent::MidiCC { channel, cc: TIMBRE_CC, value, .. } => {
+                self.voice_properties.timbre[channel as usize] = value;
+            }
+            NoteEvent::MidiPitchBend { channel, value, .. } => {
+                // The value is 0.5 when the wheel is centered
+                self.pitch_bend[channel as usize].set_target(sample_rate, value * 2.0 - 1.0);
+            }
+            NoteEvent::PolyPressure { channel, note, pressure, .. } => {
+                let key = self.voice_key(channel, note);
+                if let Some(voice) = self.voices.map.get_mut(&key) {
+                    voice.set_pressure(pressure);
+                }
+                let mono_key = self.mono_voice.as_ref().map(|v| self.voice_key(v.channel(), v.midi_note()));
+                if let Some(voice) = self.mono_voice.as_mut().filter(|_| mono_key == Some(key)) {
+                    voice.set_pressure(pressure);
+                }
+            }
+            NoteEvent::MidiChannelPressure { channel, pressure, .. } if self.mpe.is_member(channel) => {
+                // The pressure of an MPE member channel only applies to the note on that channel
+                for voice in self.voices.map.values_mut().chain(&mut self.mono_voice) {
+                    if voice.channel() == channel {
+                        voice.set_pressure(pressure);
+                    }
+                }
+            }
+            NoteEvent::MidiChannelPressure { channel, pressure, .. } => {
+                self.voice_properties.channel_pressure[channel as usize] = pressure;
+            }
+            NoteEvent::PolyVolume { voice_id, channel, note, gain, .. } => {
+                if let Some(voice) = self.find_voice(voice_id, channel, note) {
+                    voice.set_volume(gain);
+                }
+            }
+            NoteEvent::PolyPan { voice_id, channel, note, pan, .. } => {
+                if let Some(voice) = self.find_voice(voice_id, channel, note) {
+                    voice.set_pan(pan);
+                }
+            }
+            NoteEvent::PolyTuning { voice_id, channel, note, tuning, .. } => {
+                if let Some(voice) = self.find_voice(voice_id, channel, note) {
+                    voice.set_tuning(tuning);
+                }
+            }
+            NoteEvent::PolyModulation { voice_id, poly_modulation_id, normalized_offset, .. } => {
+                let Some(parameter) = ModParameter::from_index(poly_modulation_id as usize) else { return; };
+                let Some(param) = get_poly_param(params, parameter) else { return; };
+                let amount = get_poly_modulation_amount(
+                    param, parameter, param.modulated_normalized_value(), normalized_offset,
+                );
+                if let Some(voice) = self.find_voice(Some(voice_id), 0, 0) {
+                    voice.set_poly_modulation(parameter, normalized_offset, amount);
+                }
+            }
+            NoteEvent::MonoAutomation { poly_modulation_id, normalized_value, .. } => {
+                // The modulation amounts depend on the parameter's value, so they need to be
+                // recalculated for every voice
+                let Some(parameter) = ModParameter::from_index(poly_modulation_id as usize) else { return; };
+                let Some(param) = get_poly_param(params, parameter) else { return; };
+                for voice in self.voices.map.values_mut().chain(&mut self.mono_voice) {
+                    let offset = voice.poly_offset(parameter);
+                    if offset != 0.0 {
+                        let amount = get_poly_modulation_amount(param, parameter, normalized_value, offset);
+                        voice.set_poly_modulation(parameter, offset, amount);
+                    }
                 }
             }
-            // NoteEvent::PolyPressure { note, pressure, .. }  =>
-            //     {
-            //         ()
-            //     }
             _ => (),
         }
     }
 
-    fn release_note(&mut self, notes: [Note; OSCILLATOR_AMOUNT]) {
-        for mut note in notes {
-            note.release();
-            self.released_notes.push(note);
+    /// Find a held voice by its `voice_id`, or by its channel and note if there is no ID.
+    /// Voices that are releasing can only be found by their ID, because the host keeps modulating
+    /// them until they are terminated, while a new note with the same key could already be held.
+    fn find_voice(&mut self, voice_id: Option<i32>, channel: u8, note: u8) -> Option<&mut Voice> {
+        let released = self.released_voices.iter_mut()
+            .filter(|voice| voice_id.is_some() && !voice.is_stolen());
+        self.voices.map.values_mut().chain(&mut self.mono_voice).chain(released)
+            .find(|voice| match voice_id {
+                Some(voice_id) => voice.voice_id() == voice_id,
+                None => voice.channel() == channel && voice.midi_note() == note,
+            })
+    }
+
+    /// Get the key of a voice, notes on different channels only get separate voices in MPE mode
+    fn voice_key(&self, channel: u8, note: u8) -> VoiceKey {
+        if self.mpe.enabled { (channel, note) } else { (0, note) }
+    }
+
+    /// Whether a released key should keep playing because of the pedals
+    fn is_sustained(&self, key: VoiceKey) -> bool {
+        self.pedals.sustain || (self.pedals.sostenuto && self.sostenuto_keys.contains(&key))
+    }
+
+    /// Release the sustained keys that are no longer held by a pedal
+    fn release_sustained_keys(&mut self) {
+        let mut i = 0;
+        while i < self.sustained_keys.len() {
+            let key = self.sustained_keys[i];
+            if self.is_sustained(key) {
+                i += 1;
+            } else {
+                self.sustained_keys.swap_remove(i);
+                self.release_key(key);
+            }
+        }
+    }
+
+    fn release_key(&mut self, key: VoiceKey) {
+        if self.voice_mode == VoiceMode::Poly {
+            if let Some(voice) = self.voices.remove(&key) {
+                self.release_voice(voice);
+            }
+        } else {
+            self.release_mono(key);
+        }
+    }
+
+    /// Press a key in one of the monophonic voice modes
+    fn play_mono(&mut self, key: VoiceKey, voice_id: i32, channel: u8, velocity: f32, sample_rate: f32) {
+        self.remove_held_key(key);
+        self.held_keys.push(HeldKey { key, voice_id, channel, velocity });
+        let Some(next) = self.priority_key() else { return; };
+
+        match &self.mono_voice {
+            Some(voice) => {
+                let legato = voice.is_held();
+                // A key with a lower priority was pressed
+                if legato && voice.voice_id() == next.voice_id { return; }
+
+                // Legato only glides and skips the envelope attack when another key was held
+                let retrigger = self.voice_mode == VoiceMode::Mono || !legato;
+                let glide = if self.voice_mode == VoiceMode::Legato && !legato {
+                    Glide { time: 0.0, ..self.glide }
+                } else {
+                    self.glide
+                };
+                self.switch_mono_voice(next, &glide, retrigger);
+            }
+            None => {
+                self.mono_voice = Some(Voice::new(next.voice_id, next.note(), next.channel, next.velocity,
+                                                  sample_rate, &self.voice_properties, &mut self.random));
+            }
+        }
+    }
+
+    /// Release a key in one of the monophonic voice modes, which returns to the previously held
+    /// key if there is one
+    fn release_mono(&mut self, key: VoiceKey) {
+        self.remove_held_key(key);
+        let glide = self.glide;
+        let retrigger = self.voice_mode == VoiceMode::Mono;
+        match self.priority_key() {
+            Some(next) => {
+                if self.mono_voice.as_ref().is_some_and(|voice| voice.voice_id() != next.voice_id) {
+                    self.switch_mono_voice(next, &glide, retrigger);
+                }
+            }
+            None => {
+                if let Some(voice) = &mut self.mono_voice {
+                    voice.release();
+                }
+            }
+        }
+    }
+
+    /// Let the monophonic voice play the `held` key under its voice ID. The host can stop
+    /// modulating the previous note, unless its key is still held and the voice can return to it.
+    fn switch_mono_voice(&mut self, held: HeldKey, glide: &Glide, retrigger: bool) {
+        let Some(voice) = &self.mono_voice else { return; };
+        let (voice_id, channel, note) = (voice.voice_id(), voice.channel(), voice.midi_note());
+        if !self.held_keys.iter().any(|held| held.voice_id == voice_id) {
+            self.terminate_id(voice_id, channel, note);
+        }
+
+        if let Some(voice) = &mut self.mono_voice {
+            voice.set_voice_id(held.voice_id, held.channel);
+            voice.glide_to(held.note(), held.velocity, glide, retrigger);
+        }
+    }
+
+    /// Stop holding `key` in one of the monophonic voice modes. Its voice ID ends, unless the
+    /// voice is still playing it.
+    fn remove_held_key(&mut self, key: VoiceKey) {
+        let Some(index) = self.held_keys.iter().position(|held| held.key == key) else { return; };
+        let held = self.held_keys.remove(index);
+        if self.mono_voice.as_ref().map_or(true, |voice| voice.voice_id() != held.voice_id) {
+            self.terminate_id(held.voice_id, held.channel, held.note());
+        }
+    }
+
+    /// Get the held key that should be played according to the note priority
+    fn priority_key(&self) -> Option<HeldKey> {
+        let keys = self.held_keys.iter().copied();
+        match self.note_priority {
+            NotePriority::Last => self.held_keys.last().copied(),
+            NotePriority::Low => keys.min_by_key(|held| held.note()),
+            NotePriority::High => keys.max_by_key(|held| held.note()),
+        }
+    }
+
+    /// Release all voices, used when switching between voice modes
+    fn release_all(&mut self) {
+        while let Some((_, voice)) = self.voices.remove_index(0) {
+            self.release_voice(voice);
+        }
+        while let Some(held) = self.held_keys.last().copied() {
+            self.remove_held_key(held.key);
+        }
+        if let Some(voice) = self.mono_voice.take() {
+            self.release_voice(voice);
+        }
+        self.sustained_keys.clear();
+        self.sostenuto_keys.clear();
+    }
+
+    fn release_voice(&mut self, mut voice: Voice) {
+        voice.release();
+        self.push_released(voice);
+    }
+
+    /// Remove a held voice according to the voice stealing policy and fade it out
+    fn steal_voice(&mut self) {
+        let voices = &self.voices.map;
+        let index = match self.voice_stealing {
+            VoiceStealing::Oldest => Some(0),
+            VoiceStealing::Quietest => voices.values().enumerate()
+                .min_by(|(_, a), (_, b)| a.gain().total_cmp(&b.gain()))
+                .map(|(i, _)| i),
+            VoiceStealing::Lowest => voices.values().enumerate()
+                .min_by_key(|(_, v)| v.midi_note())
+                .map(|(i, _)| i),
+            VoiceStealing::Highest => voices.values().enumerate()
+                .max_by_key(|(_, v)| v.midi_note())
+                .map(|(i, _)| i),
         };
+
+        if let Some((_, mut voice)) = index.and_then(|i| self.voices.remove_index(i)) {
+            // The host can stop modulating the voice right away
+            self.terminate(&voice);
+            voice.steal();
+            self.push_released(voice);
+        }
+    }
+
+    /// Add a voice to the released voices, without growing the list beyond its capacity
+    fn push_released(&mut self, voice: Voice) {
+        if self.released_voices.len() == self.released_voices.capacity() {
+            let oldest = self.released_voices.remove(0);
+            if !oldest.is_stolen() {
+                self.terminate(&oldest);
+            }
+        }
+        self.released_voices.push(voice);
+    }
+
+    /// Remember that a voice ended, so it can be reported to the host
+    fn terminate(&mut self, voice: &Voice) {
+        self.terminate_id(voice.voice_id(), voice.channel(), voice.midi_note());
+    }
+
+    fn terminate_id(&mut self, voice_id: i32, channel: u8, note: u8) {
+        if self.terminated_voices.len() < self.terminated_voices.capacity() {
+            self.terminated_voices.push((voice_id, channel, note));
+        }
     }
 
     pub fn remove_finished_notes(&mut self) {
-        self.released_notes.retain(|n| !n.is_finished());
+        let terminated_voices = &mut self.terminated_voices;
+        self.released_voices.retain(|v| {
+            let finished = v.is_finished();
+            // Stolen voices were already reported
+            if finished && !v.is_stolen() && terminated_voices.len() < terminated_voices.capacity() {
+                terminated_voices.push((v.voice_id(), v.channel(), v.midi_note()));
+            }
+            !finished
+        });
+        if self.mono_voice.as_ref().is_some_and(|v| v.is_finished()) {
+            if let Some(voice) = self.mono_voice.take() {
+                self.terminate(&voice);
+            }
+        }
     }
 
-    pub fn get_sample_value(&mut self) -> f32 {
-        // Sum held notes
-        let mut new_sample: f32 = self.notes.map.values_mut()
-            .flat_map(|notes| {
-                notes.iter_mut().map(|note| note.get_sample())
-            }).sum();
-        // Add sum of released notes
-        new_sample += self.released_notes.iter_mut()
-            .map(|note| note.get_sample()).sum::<f32>();
+    /// Get the `VoiceTerminated` events for the voices that ended since the last call
+    pub fn drain_terminated_voices(&mut self, timing: u32) -> impl Iterator<Item=PluginNoteEvent<Synth>> + '_ {
+        self.terminated_voices.drain(..).map(move |(voice_id, channel, note)| {
+            NoteEvent::VoiceTerminated { timing, voice_id: Some(voice_id), channel, note }
+        })
+    }
+
+    /// The amount of voices that are currently playing
+    pub fn voice_count(&self) -> usize {
+        self.voices.map.len() + self.released_voices.len() + usize::from(self.mono_voice.is_some())
+    }
 
-        new_sample
+    /// Render a block of all voices, `left` and `right` have the length of the block and are
+    /// added to
+    pub fn render_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let samples = left.len();
+
+        // Advance the LFOs that are shared by all voices
+        for i in 0..LFO_AMOUNT {
+            self.voice_properties.global_lfo_values[i] =
+                self.global_lfos[i].next(&self.voice_properties.lfos[i], self.sample_rate, samples);
+        }
+
+        // Convert the pitch bend to semitones
+        for (channel, (semitones, bend)) in self.voice_properties.pitch_bend.iter_mut()
+            .zip(&self.pitch_bend).enumerate() {
+            let bend = bend.next_step(samples as u32);
+            *semitones = bend * if self.mpe.is_member(channel as u8) {
+                self.mpe.bend_range
+            } else if bend > 0.0 {
+                self.bend_up
+            } else {
+                self.bend_down
+            };
+        }
+
+        for (value, cc) in self.voice_properties.cc_values.iter_mut()
+            .zip(self.voice_properties.routing.cc_numbers) {
+            *value = self.cc_values[cc as usize];
+        }
+
+        // Render held, released and monophonic voices in groups that are processed together
+        let mut voices = self.voices.map.values_mut()
+            .chain(self.released_voices.iter_mut())
+            .chain(&mut self.mono_voice);
+        loop {
+            let lanes: [Option<&mut Voice>; LANES] = std::array::from_fn(|_| voices.next());
+            if lanes[0].is_none() { break; }
+            render_voices(lanes, &self.voice_properties, &self.wavetables, &self.samples, left, right);
+        }
+    }
+
+    /// Pick up the modulation routing, harmonics, wavetables and samples that were changed by the GUI,
+    /// returns whether anything was replaced
+    pub fn receive_updates(&mut self, routing: &SwapSlot<ModulationRouting>,
+                           harmonics: &SwapSlot<Harmonics>, wavetables: &Wavetables,
+                           samples: &Samples,
+    ) -> bool {
+        let routing_received = routing.receive(&mut self.voice_properties.routing);
+        if routing_received {
+            self.voice_properties.envelope_amounts = self.voice_properties.routing.envelope_amounts();
+        }
+        routing_received
+            | harmonics.receive(&mut self.harmonics)
+            | wavetables.receive(&mut self.wavetables)
+            | samples.receive(&mut self.samples)
     }
 
-    pub fn update(&mut self, params: &Arc<SynthParams>) {
+    /// Update the oscillator, envelope, filter and LFO parameters for a block of `samples` samples,
+    /// `tempo` is used for synced LFOs
+    pub fn update(&mut self, params: &Arc<SynthParams>, tempo: f32, samples: usize) {
+        // The smoothed parameters are advanced by a whole block at once
+        let steps = samples as u32;
+        self.voice_stealing = params.voice_stealing.value();
+        self.note_priority = params.note_priority.value();
+        self.bend_up = params.bend_up.value() as f32;
+        self.bend_down = params.bend_down.value() as f32;
+        let mpe_params = &params.mpe_params;
+        let mpe = MpeSettings {
+            enabled: mpe_params.enabled.value(),
+            zone: mpe_params.zone.value(),
+            member_channels: mpe_params.member_channels.value() as u8,
+            bend_range: mpe_params.bend_range.value() as f32,
+        };
+        if mpe.enabled != self.mpe.enabled {
+            // The voices are keyed differently in MPE mode
+            self.release_all();
+        }
+        self.mpe = mpe;
+        self.voice_properties.mpe_master = mpe.enabled.then(|| mpe.master_channel() as usize);
+        self.glide = Glide { time: params.glide_time.value(), mode: params.glide_mode.value() };
+        if params.voice_mode.value() != self.voice_mode {
+            self.release_all();
+            self.voice_mode = params.voice_mode.value();
+        }
+        self.voices.set_capacity(params.polyphony.value() as usize);
+        // Steal voices when the polyphony limit is lowered below the amount of held voices
+        while self.voices.map.len() > params.polyphony.value() as usize {
+            self.steal_voice();
+        }
+
         for i in 0..OSCILLATOR_AMOUNT {
-            let osc_params = &params.oscillator_params[i];
-            *self.oscillator_properties[i].lock().unwrap() =
-                OscillatorProperties::new(
-                    osc_params.wave_kind.value(),
-                    osc_params.pulse_width.smoothed.next(),
-                    util::db_to_gain_fast(osc_params.volume.smoothed.next()),
-                    osc_params.enabled.value(),
-                    osc_params.transpose.value(),
-                    osc_params.detune.value(),
-                );
+            self.voice_properties.oscillators[i] = OscillatorProperties::from_params(
+                &params.oscillator_params[i], &self.harmonics.0[i], steps,
+            );
         }
+        let sub_params = &params.sub_params;
+        self.voice_properties.sub = SubProperties::new(
+            sub_params.enabled.value(),
+            sub_params.shape.value(),
+            sub_params.octaves.value(),
+            util::db_to_gain_fast(sub_params.volume.smoothed.next_step(steps)),
+        );
         for i in 0..ENVELOPE_AMOUNT {
             let env_params = &params.envelope_params[i];
-            permit_alloc(|| {
-                // TODO idk how to fix this
-                self.envelope_properties.lock().unwrap()[i] =
-                    EnvelopeProperties::new(
-                        Adsr::new(
-                            env_params.attack.smoothed.next(),
-                            env_params.decay.smoothed.next(),
-                            util::db_to_gain_fast(env_params.sustain.smoothed.next()),
-                            env_params.release.smoothed.next(),
-                        ),
-                        Arc::clone(&env_params.targets),
-                    );
-            })
+            self.voice_properties.envelopes[i] = Adsr::new(
+                env_params.attack.smoothed.next_step(steps),
+                env_params.decay.smoothed.next_step(steps),
+                util::db_to_gain_fast(env_params.sustain.smoothed.next_step(steps)),
+                env_params.release.smoothed.next_step(steps),
+                env_params.attack_curve.smoothed.next_step(steps),
+                env_params.decay_curve.smoothed.next_step(steps),
+                env_params.release_curve.smoothed.next_step(steps),
+            );
+        }
+        for i in 0..LFO_AMOUNT {
+            let lfo_params = &params.lfo_params[i];
+            let frequency = if lfo_params.sync.value() {
+                tempo / 60.0 / lfo_params.division.value().beats()
+            } else {
+                lfo_params.rate.smoothed.next_step(steps)
+            };
+            self.voice_properties.lfos[i] = LfoProperties::new(
+                lfo_params.enabled.value(),
+                lfo_params.wave_kind.value(),
+                frequency,
+                lfo_params.depth.smoothed.next_step(steps),
+                lfo_params.phase.value(),
+                lfo_params.retrigger.value(),
+            );
+        }
+        self.voice_properties.spread = params.stereo_spread.smoothed.next_step(steps);
+        let filter_params = &params.filter_params;
+        self.voice_properties.filter = FilterProperties::new(
+            filter_params.enabled.value(),
+            filter_params.mode.value(),
+            filter_params.cutoff.smoothed.next_step(steps),
+            filter_params.resonance.smoothed.next_step(steps),
+            filter_params.keytrack.value(),
+        );
+    }
+}
+
+/// The voice ID to use when the host does not provide one
+fn compute_fallback_voice_id(note: u8, channel: u8) -> i32 {
+    note as i32 | ((channel as i32) << 16)
+}
+
+/// Get the parameter that can be modulated polyphonically for `parameter`
+fn get_poly_param(params: &SynthParams, parameter: ModParameter) -> Option<&FloatParam> {
+    match parameter {
+        ModParameter::OscillatorVolume(i) => Some(&params.oscillator_params[i].volume),
+        ModParameter::OscillatorDetune(i) => Some(&params.oscillator_params[i].detune),
+        ModParameter::OscillatorPulseWidth(i) => Some(&params.oscillator_params[i].pulse_width),
+        _ => None,
+    }
+}
+
+/// Convert a normalized polyphonic modulation `offset` to a modulation amount, relative to the
+/// parameter's `normalized_value`
+fn get_poly_modulation_amount(param: &FloatParam, parameter: ModParameter,
+                              normalized_value: f32, offset: f32,
+) -> f32 {
+    let value = param.preview_plain(normalized_value);
+    let modulated = param.preview_plain((normalized_value + offset).clamp(0.0, 1.0));
+    match parameter {
+        ModParameter::OscillatorVolume(_) => {
+            // Volume modulation is relative to the oscillator's gain
+            let gain = util::db_to_gain(value);
+            if gain > 0.0 { util::db_to_gain(modulated) / gain - 1.0 } else { 0.0 }
         }
+        ModParameter::OscillatorDetune(_) => (modulated - value) / DETUNE_RANGE,
+        _ => modulated - value,
     }
 }
@@ -0,0 +1,29 @@
+//! Polynomial corrections for rendering band-limited waves from their naive versions.
+//! `t` is the phase relative to the discontinuity and `dt` the phase increment per sample.
+
+/// Residual of a band-limited step (PolyBLEP), for a step with a height of 2.
+pub fn poly_blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Residual of a band-limited ramp (PolyBLAMP, the integrated PolyBLEP), for a change in slope
+/// of 2 per sample.
+pub fn poly_blamp(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let t = t / dt - 1.0;
+        -t * t * t / 3.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt + 1.0;
+        t * t * t / 3.0
+    } else {
+        0.0
+    }
+}
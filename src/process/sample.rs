@@ -0,0 +1,239 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use enum_iterator::Sequence;
+use crate::OSCILLATOR_AMOUNT;
+use crate::utils::swap_slot::SwapSlot;
+
+/// An audio file that is played back by an oscillator, mixed down to mono
+pub struct Sample {
+    data: Vec<f32>,
+    sample_rate: f32,
+}
+
+impl Sample {
+    /// Loads a WAV or FLAC file, depending on the extension
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SampleError> {
+        let path = path.as_ref();
+        let is_flac = path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("flac"));
+        if is_flac {
+            Self::from_flac(path)
+        } else {
+            Self::from_wav(path)
+        }
+    }
+
+    fn from_wav(path: &Path) -> Result<Self, SampleError> {
+        let mut reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => {
+                reader.samples::<f32>().collect::<Result<_, _>>()?
+            }
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+                reader.samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        Self::from_interleaved(&samples, spec.channels as usize, spec.sample_rate as f32)
+    }
+
+    fn from_flac(path: &Path) -> Result<Self, SampleError> {
+        let mut reader = claxon::FlacReader::open(path)?;
+        let info = reader.streaminfo();
+        let scale = 1.0 / (1u64 << (info.bits_per_sample - 1)) as f32;
+
+        let samples: Vec<f32> = reader.samples()
+            .map(|s| s.map(|s| s as f32 * scale))
+            .collect::<Result<_, _>>()?;
+
+        Self::from_interleaved(&samples, info.channels as usize, info.sample_rate as f32)
+    }
+
+    /// Mix the interleaved `channels` of `samples` down to mono
+    fn from_interleaved(samples: &[f32], channels: usize, sample_rate: f32) -> Result<Self, SampleError> {
+        if samples.len() < channels.max(1) {
+            return Err(SampleError::Empty);
+        }
+
+        let data = samples.chunks_exact(channels.max(1))
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+
+        Ok(Self { data, sample_rate })
+    }
+
+    /// How many frames the position moves for a phase delta of one cycle, so the sample is played
+    /// back at its original speed at the `root_key`
+    pub fn frames_per_cycle(&self, root_key: f32) -> f64 {
+        self.sample_rate as f64 / nih_plug::util::f32_midi_note_to_freq(root_key) as f64
+    }
+
+    /// The frame at `position`, which goes from 0 (the start) to 1 (the end)
+    pub fn frame_at(&self, position: f32) -> f64 {
+        position as f64 * self.data.len() as f64
+    }
+
+    /// Get the value at `frame`, crossfading into the start of the loop at the end of the loop
+    pub fn sample(&self, frame: f64, playback: &SamplePlayback) -> f32 {
+        let value = self.read(frame);
+        let Some((loop_start, loop_end, fade)) = self.loop_frames(playback) else { return value; };
+
+        // The end of the loop fades into the first frames of the loop, which are skipped when
+        // jumping back, so the crossfade does not need any material outside of the loop
+        let fade_start = loop_end - fade;
+        if fade > 0.0 && frame > fade_start && frame < loop_end {
+            let t = ((frame - fade_start) / fade) as f32;
+            value * (1.0 - t) + self.read(loop_start + (frame - fade_start)) * t
+        } else {
+            value
+        }
+    }
+
+    /// Move `frame` forward by `step` frames, jumping back to the loop start at the loop end
+    pub fn advance(&self, frame: f64, step: f64, playback: &SamplePlayback) -> f64 {
+        let frame = frame + step;
+        match self.loop_frames(playback) {
+            Some((loop_start, loop_end, fade)) if frame >= loop_end => {
+                // Continue after the frames that were already faded in
+                loop_start + fade + (frame - loop_end) % (loop_end - loop_start - fade)
+            }
+            // A one shot sample stays silent after the end
+            _ => frame.min(self.data.len() as f64),
+        }
+    }
+
+    /// The first and last frame of the loop and the length of the crossfade, or `None` if the
+    /// sample does not loop. The crossfade is at most half of the loop.
+    fn loop_frames(&self, playback: &SamplePlayback) -> Option<(f64, f64, f64)> {
+        playback.loop_points().map(|(loop_start, loop_end)| {
+            let (loop_start, loop_end) = (self.frame_at(loop_start), self.frame_at(loop_end));
+            let fade = playback.crossfade.clamp(0.0, 0.5) as f64 * (loop_end - loop_start);
+            (loop_start, loop_end, fade)
+        })
+    }
+
+    /// Read the data with linear interpolation, which is silent outside of the sample
+    fn read(&self, frame: f64) -> f32 {
+        if !(0.0..self.data.len() as f64).contains(&frame) { return 0.0; }
+
+        let index = frame as usize;
+        let t = (frame - index as f64) as f32;
+        let current = self.data[index];
+        let next = self.data.get(index + 1).copied().unwrap_or(0.0);
+        current + (next - current) * t
+    }
+}
+
+#[derive(Debug)]
+pub enum SampleError {
+    Wav(hound::Error),
+    Flac(claxon::Error),
+    Empty,
+}
+
+impl Display for SampleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SampleError::Wav(e) => write!(f, "{e}"),
+            SampleError::Flac(e) => write!(f, "{e}"),
+            SampleError::Empty => write!(f, "the file does not contain any samples"),
+        }
+    }
+}
+
+impl From<hound::Error> for SampleError {
+    fn from(value: hound::Error) -> Self {
+        SampleError::Wav(value)
+    }
+}
+
+impl From<claxon::Error> for SampleError {
+    fn from(value: claxon::Error) -> Self {
+        SampleError::Flac(value)
+    }
+}
+
+/// Whether a sample plays once or keeps repeating the loop
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum SampleMode {
+    #[id = "oneshot"]
+    #[name = "One shot"]
+    OneShot,
+    #[id = "loop"]
+    #[name = "Loop"]
+    Loop,
+}
+
+/// How an oscillator plays its sample. The positions go from 0 (the start of the sample) to 1
+/// (the end).
+#[derive(Clone, Copy)]
+pub struct SamplePlayback {
+    /// The note at which the sample plays at its original speed
+    pub root_key: f32,
+    pub start: f32,
+    pub loop_start: f32,
+    pub loop_end: f32,
+    /// The length of the crossfade at the end of the loop, relative to the loop length, up to 0.5
+    pub crossfade: f32,
+    pub mode: SampleMode,
+}
+
+impl SamplePlayback {
+    pub fn new(root_key: f32, start: f32, loop_start: f32, loop_end: f32, crossfade: f32,
+               mode: SampleMode,
+    ) -> Self {
+        Self { root_key, start, loop_start, loop_end, crossfade, mode }
+    }
+
+    /// The start and end of the loop, or `None` if the sample does not loop
+    fn loop_points(&self) -> Option<(f32, f32)> {
+        (self.mode == SampleMode::Loop && self.loop_end > self.loop_start)
+            .then_some((self.loop_start, self.loop_end))
+    }
+}
+
+impl Default for SamplePlayback {
+    fn default() -> Self {
+        Self::new(60.0, 0.0, 0.0, 1.0, 0.0, SampleMode::OneShot)
+    }
+}
+
+/// The loaded sample of every oscillator, which are prepared by a background task and handed to
+/// the audio thread without locking.
+pub struct Samples {
+    samples: [SwapSlot<Option<Sample>>; OSCILLATOR_AMOUNT],
+}
+
+impl Samples {
+    pub fn new() -> Self {
+        Self {
+            samples: std::array::from_fn(|_| SwapSlot::new()),
+        }
+    }
+
+    pub fn set(&self, index: usize, sample: Option<Sample>) {
+        self.samples[index].send(sample);
+    }
+
+    /// Replace the `current` samples of the audio thread with the ones that were changed, returns
+    /// whether any sample was replaced
+    pub fn receive(&self, current: &mut [Option<Sample>; OSCILLATOR_AMOUNT]) -> bool {
+        let mut received = false;
+        for (slot, sample) in self.samples.iter().zip(current) {
+            received |= slot.receive(sample);
+        }
+        received
+    }
+
+    /// Drop the samples that were replaced on the audio thread
+    pub fn collect_retired(&self) {
+        for slot in &self.samples {
+            slot.collect_retired();
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use enum_iterator::Sequence;
+use crate::process::note::{get_bandlimited_wave_lanes, get_wave_lanes, WaveKind};
+use crate::utils::f32x4::F32x4;
+
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum SubShape {
+    #[id = "sine"]
+    #[name = "Sine"]
+    Sine,
+    #[id = "square"]
+    #[name = "Square"]
+    Square,
+}
+
+/// The settings of the sub-oscillator, which every voice has besides the regular oscillators
+#[derive(Clone, Copy)]
+pub struct SubProperties {
+    pub enabled: bool,
+    pub shape: SubShape,
+    /// How many octaves the sub-oscillator is below the played note
+    pub octaves: i32,
+    pub volume: f32,
+}
+
+impl SubProperties {
+    pub fn new(enabled: bool, shape: SubShape, octaves: i32, volume: f32) -> Self {
+        Self {
+            enabled,
+            shape,
+            octaves,
+            volume,
+        }
+    }
+}
+
+impl Default for SubProperties {
+    fn default() -> Self {
+        Self::new(false, SubShape::Sine, 1, 1.0)
+    }
+}
+
+/// The sub-oscillators of up to [`LANES`](crate::utils::f32x4::LANES) voices during a block
+pub struct SubBlock {
+    pub phase: F32x4,
+    pub phase_delta: F32x4,
+    /// The volume, including the velocity
+    pub gain: F32x4,
+}
+
+impl SubBlock {
+    /// Get the next sample of every lane
+    pub fn next(&mut self, properties: &SubProperties) -> F32x4 {
+        let wave = match properties.shape {
+            SubShape::Sine => get_wave_lanes(WaveKind::Sine, self.phase, F32x4::ZERO),
+            SubShape::Square => get_bandlimited_wave_lanes(
+                WaveKind::Square, self.phase, self.phase_delta, F32x4::splat(0.5),
+            ),
+        };
+        self.phase = (self.phase + self.phase_delta).fract();
+        wave * self.gain
+    }
+}
@@ -0,0 +1,82 @@
+use enum_iterator::Sequence;
+use crate::MAX_UNISON;
+use crate::process::voice::pan_gains;
+
+/// How the detune is distributed over the unison voices
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum DetuneCurve {
+    /// The voices are evenly spaced
+    #[id = "linear"]
+    #[name = "Linear"]
+    Linear,
+    /// The inner voices stay close to the played pitch, only the outer voices are detuned a lot
+    #[id = "exponential"]
+    #[name = "Exp"]
+    Exponential,
+}
+
+/// The unison settings of an oscillator, with the tuning and gain of every unison voice
+#[derive(Clone, Copy)]
+pub struct Unison {
+    pub voices: usize,
+    /// The frequency ratio of every voice to the played pitch
+    pub ratios: [f32; MAX_UNISON],
+    /// The left and right gain of every voice
+    pub gains: [[f32; 2]; MAX_UNISON],
+}
+
+impl Unison {
+    /// `detune` is the detune of the outer voices in cents, `width` (0 to 1) spreads the voices
+    /// across the stereo field around `pan`, and `blend` fades from only the centre voices (0)
+    /// to only the side voices (1).
+    pub fn new(voices: usize, detune: f32, curve: DetuneCurve, width: f32, blend: f32, pan: f32) -> Self {
+        let voices = voices.clamp(1, MAX_UNISON);
+        let mut ratios = [1.0; MAX_UNISON];
+        let mut gains = [[0.0; 2]; MAX_UNISON];
+
+        let mut total_power = 0.0;
+        for voice in 0..voices {
+            // The position from -1 to 1
+            let position = if voices == 1 {
+                0.0
+            } else {
+                voice as f32 / (voices - 1) as f32 * 2.0 - 1.0
+            };
+            let detune = detune * match curve {
+                DetuneCurve::Linear => position,
+                DetuneCurve::Exponential => position * position.abs(),
+            };
+            ratios[voice] = 2f32.powf(detune / 1200.0);
+
+            // The middle voice, or the middle two voices for an even amount, are the centre.
+            // Without side voices there is nothing to blend.
+            let is_centre = (voice as f32 - (voices - 1) as f32 / 2.0).abs() < 1.0;
+            let level = if voices <= 2 {
+                1.0
+            } else if is_centre {
+                (2.0 - blend * 2.0).min(1.0)
+            } else {
+                (blend * 2.0).min(1.0)
+            };
+            total_power += level * level;
+
+            gains[voice] = pan_gains(pan + position * width).map(|gain| gain * level);
+        }
+
+        // Keep the loudness the same regardless of the amount of voices
+        if total_power > 0.0 {
+            let normalize = total_power.sqrt().recip();
+            for gain in gains.iter_mut().flatten() {
+                *gain *= normalize;
+            }
+        }
+
+        Self { voices, ratios, gains }
+    }
+}
+
+impl Default for Unison {
+    fn default() -> Self {
+        Self::new(1, 0.0, DetuneCurve::Linear, 0.0, 0.5, 0.0)
+    }
+}
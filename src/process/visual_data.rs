@@ -1,4 +1,5 @@
 use nih_plug::util;
+use crate::process::notes::Pedals;
 
 pub struct SynthData {
     data: triple_buffer::Input<VisualData>,
@@ -21,6 +22,8 @@ impl SynthData {
 pub struct VisualData {
     pub peak_meter: f32,
     pub samples: Vec<f32>,
+    pub pedals: Pedals,
+    pub voice_count: usize,
 }
 
 impl Default for VisualData {
@@ -28,6 +31,8 @@ impl Default for VisualData {
         Self {
             peak_meter: util::MINUS_INFINITY_DB,
             samples: Vec::with_capacity(512),
+            pedals: Pedals::default(),
+            voice_count: 0,
         }
     }
 }
@@ -35,6 +40,8 @@ impl Default for VisualData {
 impl SynthData {
     pub fn set_visual_data(&mut self,
                            new_sample: f32,
+                           pedals: Pedals,
+                           voice_count: usize,
     ) {
         // Load data input buffer
         let data = self.data.input_buffer();
@@ -55,6 +62,9 @@ impl SynthData {
         }
         data.samples.push(new_sample);
 
+        data.pedals = pedals;
+        data.voice_count = voice_count;
+
         // Publish input buffer
         self.data.publish();
     }
@@ -0,0 +1,482 @@
+use enum_iterator::Sequence;
+use nih_plug::util;
+use crate::{CC_SOURCE_AMOUNT, ENVELOPE_AMOUNT, LFO_AMOUNT, MAX_BLOCK_SIZE, MIDI_CHANNELS, OSCILLATOR_AMOUNT};
+use crate::params::envelope_target::{ModParameter, Target};
+use crate::process::envelope::{Adsr, Stage};
+use crate::process::filter::{Filter, FilterCoefficients, FilterProperties};
+use crate::process::lfo::{Lfo, LfoProperties, LfoRetrigger};
+use crate::process::modulation::{ModulationRouting, OscillatorModulation, SourceValues};
+use crate::process::note::{Note, OscillatorBlock, OscillatorProperties, render_notes};
+use crate::process::sample::Sample;
+use crate::process::sub_oscillator::{SubBlock, SubProperties};
+use crate::process::wavetable::Wavetable;
+use crate::utils::f32x4::{F32x4, LANES};
+use crate::utils::random::Random;
+
+/// A single played key: the notes of all oscillators, which are filtered together.
+pub struct Voice {
+    /// The ID the host uses to refer to this voice
+    voice_id: i32,
+    midi_note: u8,
+    channel: u8,
+    /// The polyphonic aftertouch of this voice
+    pressure: f32,
+    /// The gain, panning (-1 to 1) and tuning (in semitones) set by the host for this voice only
+    volume: f32,
+    pan: f32,
+    tuning: f32,
+    /// The normalized offsets of the polyphonically modulated parameters of each oscillator,
+    /// which are kept so the modulation can be updated when the parameter changes
+    poly_offsets: [[f32; POLY_PARAMETERS]; OSCILLATOR_AMOUNT],
+    /// The polyphonic modulation of each oscillator, converted from `poly_offsets`
+    poly_modulation: [OscillatorModulation; OSCILLATOR_AMOUNT],
+    /// The current note number, which glides towards `midi_note`
+    pitch: f32,
+    /// The amount of semitones the pitch moves each sample while gliding
+    glide_step: f32,
+    sample_rate: f32,
+    notes: [Note; OSCILLATOR_AMOUNT],
+    sub_phase: f32,
+    /// The filters of the left and right channel
+    filters: [Filter; 2],
+    lfos: [Lfo; LFO_AMOUNT],
+    /// The remaining gain of a voice that is fading out after being stolen
+    fade_out: Option<f32>,
+}
+
+/// The time it takes for a stolen voice to fade out
+const STEAL_FADE_MS: f32 = 5.0;
+/// The distance from C4 in semitones at which the stereo spread pans a note completely to one side
+const SPREAD_RANGE: f32 = 24.0;
+/// The amount of oscillator parameters that can be modulated polyphonically: volume, detune and
+/// pulse width
+const POLY_PARAMETERS: usize = 3;
+
+impl Voice {
+    pub fn new(voice_id: i32, midi_note: u8, channel: u8, velocity: f32, sample_rate: f32,
+               properties: &VoiceProperties, random: &mut Random,
+    ) -> Self {
+        // Create new waves (for each oscillator) for this note
+        let notes = std::array::from_fn(|i| {
+            let phases = properties.oscillators[i].initial_phases(random);
+            Note::new(midi_note, velocity, sample_rate, i, phases, random.next_u32())
+        });
+
+        Self {
+            voice_id,
+            midi_note,
+            channel,
+            pressure: 0.0,
+            volume: 1.0,
+            pan: 0.0,
+            tuning: 0.0,
+            poly_offsets: [[0.0; POLY_PARAMETERS]; OSCILLATOR_AMOUNT],
+            poly_modulation: [OscillatorModulation::default(); OSCILLATOR_AMOUNT],
+            pitch: midi_note as f32,
+            glide_step: 0.0,
+            sample_rate,
+            notes,
+            sub_phase: 0.0,
+            filters: Default::default(),
+            lfos: [Lfo::default(); LFO_AMOUNT],
+            fade_out: None,
+        }
+    }
+
+    pub fn voice_id(&self) -> i32 {
+        self.voice_id
+    }
+
+    /// Let this voice continue under a new ID, used when a monophonic voice plays a new note
+    pub fn set_voice_id(&mut self, voice_id: i32, channel: u8) {
+        self.voice_id = voice_id;
+        self.channel = channel;
+    }
+
+    pub fn midi_note(&self) -> u8 {
+        self.midi_note
+    }
+
+    /// Change the note of this voice, used for the monophonic voice modes.
+    /// The pitch slides to the new note according to `glide`, and the envelopes are restarted if
+    /// `retrigger` is set.
+    pub fn glide_to(&mut self, midi_note: u8, velocity: f32, glide: &Glide, retrigger: bool) {
+        self.midi_note = midi_note;
+
+        let distance = (midi_note as f32 - self.pitch).abs();
+        let glide_samples = glide.time * self.sample_rate;
+        if glide_samples < 1.0 || distance == 0.0 {
+            self.pitch = midi_note as f32;
+            self.glide_step = 0.0;
+        } else {
+            self.glide_step = match glide.mode {
+                GlideMode::ConstantTime => distance / glide_samples,
+                GlideMode::ConstantRate => 12.0 / glide_samples,
+            };
+        }
+
+        if retrigger {
+            for note in &mut self.notes {
+                note.retrigger(velocity);
+            }
+            self.lfos = [Lfo::default(); LFO_AMOUNT];
+        }
+    }
+
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    pub fn set_pressure(&mut self, pressure: f32) {
+        self.pressure = pressure;
+    }
+
+    pub fn set_volume(&mut self, gain: f32) {
+        self.volume = gain;
+    }
+
+    pub fn set_pan(&mut self, pan: f32) {
+        self.pan = pan;
+    }
+
+    pub fn set_tuning(&mut self, tuning: f32) {
+        self.tuning = tuning;
+    }
+
+    /// Get the normalized polyphonic modulation offset of `parameter`
+    pub fn poly_offset(&self, parameter: ModParameter) -> f32 {
+        match parameter {
+            ModParameter::OscillatorVolume(i) => self.poly_offsets[i][0],
+            ModParameter::OscillatorDetune(i) => self.poly_offsets[i][1],
+            ModParameter::OscillatorPulseWidth(i) => self.poly_offsets[i][2],
+            _ => 0.0,
+        }
+    }
+
+    /// Set the polyphonic modulation of `parameter`, with both the normalized `offset` and the
+    /// resulting modulation `amount`
+    pub fn set_poly_modulation(&mut self, parameter: ModParameter, offset: f32, amount: f32) {
+        match parameter {
+            ModParameter::OscillatorVolume(i) => {
+                self.poly_offsets[i][0] = offset;
+                self.poly_modulation[i].volume = amount;
+            }
+            ModParameter::OscillatorDetune(i) => {
+                self.poly_offsets[i][1] = offset;
+                self.poly_modulation[i].detune = amount;
+            }
+            ModParameter::OscillatorPulseWidth(i) => {
+                self.poly_offsets[i][2] = offset;
+                self.poly_modulation[i].pulse_width = amount;
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the voice is still held, or has been released
+    pub fn is_held(&self) -> bool {
+        self.notes.iter().any(|note| note.stage == Stage::Held)
+    }
+
+    /// The current envelope gain of the loudest oscillator
+    pub fn gain(&self) -> f32 {
+        self.notes.iter().map(|note| note.last_env_gain).fold(0.0, f32::max)
+    }
+
+    /// Whether the voice was stolen and is fading out
+    pub fn is_stolen(&self) -> bool {
+        self.fade_out.is_some()
+    }
+
+    /// Quickly fade out the voice, so it can be replaced without clicking
+    pub fn steal(&mut self) {
+        self.release();
+        self.fade_out = Some(1.0);
+    }
+
+    pub fn release(&mut self) {
+        for note in &mut self.notes {
+            note.release();
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.fade_out.is_some_and(|gain| gain <= 0.0)
+            || self.notes.iter().all(|note| note.is_finished())
+    }
+
+    /// Calculate the values that stay the same during a block of `samples` samples, like the
+    /// modulation. This also advances the LFOs and glide to the end of the block.
+    fn prepare_block(&mut self, properties: &VoiceProperties, samples: usize) -> VoiceBlock {
+        let lfo_values = std::array::from_fn(|i| {
+            match properties.lfos[i].retrigger {
+                LfoRetrigger::Voice => self.lfos[i].next(&properties.lfos[i], self.sample_rate, samples),
+                LfoRetrigger::Global => properties.global_lfo_values[i],
+            }
+        });
+
+        // Move the pitch towards the played note
+        let target = self.midi_note as f32;
+        let glide_step = self.glide_step * samples as f32;
+        if self.pitch < target {
+            self.pitch = (self.pitch + glide_step).min(target);
+        } else if self.pitch > target {
+            self.pitch = (self.pitch - glide_step).max(target);
+        }
+
+        let channel = self.channel as usize;
+        let mut pitch_bend = properties.pitch_bend[channel];
+        let mut channel_pressure = properties.channel_pressure[channel];
+        // In MPE mode the master channel applies to all notes
+        if let Some(master) = properties.mpe_master.filter(|master| *master != channel) {
+            pitch_bend += properties.pitch_bend[master];
+            channel_pressure = properties.channel_pressure[master];
+        }
+        let pitch = self.pitch + pitch_bend + self.tuning;
+        let source_values = SourceValues {
+            lfos: lfo_values,
+            ccs: properties.cc_values,
+            poly_pressure: self.pressure,
+            channel_pressure,
+            timbre: properties.timbre[channel],
+        };
+
+        let oscillators = std::array::from_fn(|i| {
+            let note = &mut self.notes[i];
+            note.set_pitch(pitch);
+            let mut modulation = OscillatorModulation::from_amounts(
+                properties.routing.get_modulation(&source_values, OscillatorModulation::targets(i))
+            );
+            modulation += &self.poly_modulation[i];
+            note.prepare_block(&modulation, properties)
+        });
+
+        // All notes share the same envelope timing, so any of them can be used for the
+        // modulation of voice-wide parameters
+        let parameters = [ModParameter::FilterCutoff, ModParameter::FilterResonance];
+        let [env_cutoff, env_resonance] = self.notes[0].get_parameter_modulation(parameters, properties);
+        let [source_cutoff, source_resonance] = properties.routing.get_modulation(
+            &source_values,
+            parameters.map(|parameter| Target::Parameter(parameter.index())),
+        );
+        let filter_properties = properties.filter.modulated(
+            env_cutoff + source_cutoff,
+            env_resonance + source_resonance,
+        );
+
+        let sub_pitch = pitch - 12.0 * properties.sub.octaves as f32;
+        VoiceBlock {
+            oscillators,
+            sub_phase_delta: util::f32_midi_note_to_freq(sub_pitch) / self.sample_rate,
+            sub_gain: properties.sub.volume * self.notes[0].velocity(),
+            filter: FilterCoefficients::new(&filter_properties, pitch, self.sample_rate),
+            pan: pan_gains(self.pan + self.spread_position() * properties.spread),
+        }
+    }
+
+    /// Where the stereo spread places this voice, from -1 (left) to 1 (right), depending on
+    /// how far the note is from C4
+    fn spread_position(&self) -> f32 {
+        ((self.pitch - 60.0) / SPREAD_RANGE).clamp(-1.0, 1.0)
+    }
+
+    /// Filter and pan the summed stereo oscillator `samples` of a block, and add them to the
+    /// output
+    fn finish_block(&mut self, block: &VoiceBlock, samples: impl Iterator<Item=[f32; 2]>,
+                    left: &mut [f32], right: &mut [f32],
+    ) {
+        for ((sample, left), right) in samples.zip(left).zip(right) {
+            let mut sample = sample;
+            if let Some(coefficients) = &block.filter {
+                for (sample, filter) in sample.iter_mut().zip(&mut self.filters) {
+                    *sample = filter.process(*sample, coefficients);
+                }
+            }
+
+            let mut gain = self.volume;
+            if let Some(fade_gain) = &mut self.fade_out {
+                gain *= *fade_gain;
+                *fade_gain = (*fade_gain - 1000.0 / (STEAL_FADE_MS * self.sample_rate)).max(0.0);
+            }
+
+            *left += sample[0] * gain * block.pan[0];
+            *right += sample[1] * gain * block.pan[1];
+        }
+    }
+}
+
+/// The values of a voice that stay the same during a block
+#[derive(Clone, Copy, Default)]
+struct VoiceBlock {
+    oscillators: [OscillatorBlock; OSCILLATOR_AMOUNT],
+    sub_phase_delta: f32,
+    /// The volume of the sub-oscillator, including the velocity
+    sub_gain: f32,
+    /// `None` if the filter is disabled
+    filter: Option<FilterCoefficients>,
+    pan: [f32; 2],
+}
+
+/// Render a block of up to [`LANES`] voices, of which the oscillators are processed together.
+/// The output is added to `left` and `right`, which have the length of the block.
+pub fn render_voices(mut voices: [Option<&mut Voice>; LANES], properties: &VoiceProperties,
+                     wavetables: &[Option<Wavetable>; OSCILLATOR_AMOUNT],
+                     samples: &[Option<Sample>; OSCILLATOR_AMOUNT],
+                     left: &mut [f32], right: &mut [f32],
+) {
+    let samples = left.len();
+    let blocks: [VoiceBlock; LANES] = std::array::from_fn(|lane| match &mut voices[lane] {
+        Some(voice) => voice.prepare_block(properties, samples),
+        None => VoiceBlock::default(),
+    });
+
+    // The summed stereo oscillators of every voice
+    let mut output = [[F32x4::ZERO; 2]; MAX_BLOCK_SIZE];
+    let output = &mut output[..samples];
+    let mut sub = SubBlock {
+        phase: F32x4::from_fn(|lane| voices[lane].as_ref().map_or(0.0, |voice| voice.sub_phase)),
+        phase_delta: F32x4::from_fn(|lane| blocks[lane].sub_phase_delta),
+        gain: F32x4::from_fn(|lane| blocks[lane].sub_gain),
+    };
+    let mut notes = voices.iter_mut()
+        .map(|voice| voice.as_deref_mut().map(|voice| &mut voice.notes));
+    let notes = std::array::from_fn(|_| notes.next().flatten());
+    let oscillator_blocks = std::array::from_fn(|lane| blocks[lane].oscillators);
+    render_notes(notes, &oscillator_blocks, properties, wavetables, samples, &mut sub, output);
+
+    for (lane, voice) in voices.iter_mut().enumerate() {
+        if let Some(voice) = voice {
+            voice.sub_phase = sub.phase.0[lane];
+            let samples = output.iter().map(|[left, right]| [left.0[lane], right.0[lane]]);
+            voice.finish_block(&blocks[lane], samples, left, right);
+        }
+    }
+}
+
+/// Get the gain of the left and right channel for `pan` (-1 to 1), using an equal power pan law
+/// which keeps both channels at unity gain when centered
+pub fn pan_gains(pan: f32) -> [f32; 2] {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    [angle.cos() * std::f32::consts::SQRT_2, angle.sin() * std::f32::consts::SQRT_2]
+}
+
+/// A snapshot of the properties that are shared by all voices, which is updated every block
+#[derive(Clone, Copy)]
+pub struct VoiceProperties {
+    pub oscillators: [OscillatorProperties; OSCILLATOR_AMOUNT],
+    pub envelopes: [Adsr; ENVELOPE_AMOUNT],
+    pub routing: ModulationRouting,
+    /// How much each envelope shapes the volume of each oscillator, see
+    /// [`ModulationRouting::envelope_amounts`]
+    pub envelope_amounts: [[f32; ENVELOPE_AMOUNT]; OSCILLATOR_AMOUNT],
+    pub filter: FilterProperties,
+    pub sub: SubProperties,
+    /// How far the notes are spread across the stereo field, from 0 to 1
+    pub spread: f32,
+    pub lfos: [LfoProperties; LFO_AMOUNT],
+    /// The current values of the LFOs that are shared by all voices
+    pub global_lfo_values: [f32; LFO_AMOUNT],
+    /// The pitch bend of each MIDI channel in semitones
+    pub pitch_bend: [f32; MIDI_CHANNELS],
+    /// The current values of the CC sources
+    pub cc_values: [f32; CC_SOURCE_AMOUNT],
+    /// The channel pressure of each MIDI channel
+    pub channel_pressure: [f32; MIDI_CHANNELS],
+    /// The MPE timbre (CC74) of each MIDI channel
+    pub timbre: [f32; MIDI_CHANNELS],
+    /// The MPE master channel, of which the pitch bend and pressure apply to all voices
+    pub mpe_master: Option<usize>,
+}
+
+impl Default for VoiceProperties {
+    fn default() -> Self {
+        Self {
+            oscillators: [OscillatorProperties::default(); OSCILLATOR_AMOUNT],
+            envelopes: [Adsr::default(); ENVELOPE_AMOUNT],
+            routing: ModulationRouting::default(),
+            envelope_amounts: [[0.0; ENVELOPE_AMOUNT]; OSCILLATOR_AMOUNT],
+            filter: FilterProperties::default(),
+            sub: SubProperties::default(),
+            spread: 0.0,
+            lfos: [LfoProperties::default(); LFO_AMOUNT],
+            global_lfo_values: [0.0; LFO_AMOUNT],
+            pitch_bend: [0.0; MIDI_CHANNELS],
+            cc_values: [0.0; CC_SOURCE_AMOUNT],
+            channel_pressure: [0.0; MIDI_CHANNELS],
+            timbre: [0.0; MIDI_CHANNELS],
+            mpe_master: None,
+        }
+    }
+}
+
+/// Which voice gets replaced when a note is played while the polyphony limit is reached
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum VoiceStealing {
+    #[id = "oldest"]
+    #[name = "Oldest"]
+    Oldest,
+    #[id = "quietest"]
+    #[name = "Quietest"]
+    Quietest,
+    #[id = "lowest"]
+    #[name = "Lowest"]
+    Lowest,
+    #[id = "highest"]
+    #[name = "Highest"]
+    Highest,
+}
+
+/// How the played notes are assigned to voices
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum VoiceMode {
+    #[id = "poly"]
+    #[name = "Poly"]
+    Poly,
+    /// A single voice, which restarts the envelopes for every note
+    #[id = "mono"]
+    #[name = "Mono"]
+    Mono,
+    /// A single voice, which only restarts the envelopes if no other key was held
+    #[id = "legato"]
+    #[name = "Legato"]
+    Legato,
+}
+
+/// Which of the held keys is played in the monophonic voice modes
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum NotePriority {
+    #[id = "last"]
+    #[name = "Last"]
+    Last,
+    #[id = "low"]
+    #[name = "Low"]
+    Low,
+    #[id = "high"]
+    #[name = "High"]
+    High,
+}
+
+#[derive(nih_plug::prelude::Enum, PartialEq, Clone, Copy, Sequence)]
+pub enum GlideMode {
+    /// Every glide takes the glide time, regardless of the distance
+    #[id = "time"]
+    #[name = "Time"]
+    ConstantTime,
+    /// The glide time is the time it takes to glide an octave
+    #[id = "rate"]
+    #[name = "Rate"]
+    ConstantRate,
+}
+
+#[derive(Clone, Copy)]
+pub struct Glide {
+    /// The glide time in seconds
+    pub time: f32,
+    pub mode: GlideMode,
+}
+
+impl Default for Glide {
+    fn default() -> Self {
+        Self { time: 0.0, mode: GlideMode::ConstantTime }
+    }
+}
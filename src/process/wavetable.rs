@@ -0,0 +1,192 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+use crate::OSCILLATOR_AMOUNT;
+use crate::utils::swap_slot::SwapSlot;
+
+/// The amount of samples in a single frame (cycle) of a wavetable, the same as Serum uses.
+pub const FRAME_SIZE: usize = 2048;
+/// The amount of band-limited copies of every frame, each containing half the harmonics of the
+/// previous one.
+const MIPMAP_LEVELS: usize = 11;
+
+pub struct Wavetable {
+    frame_count: usize,
+    /// Every frame is stored `MIPMAP_LEVELS` times, with `FRAME_SIZE` samples per level
+    data: Vec<f32>,
+}
+
+impl Wavetable {
+    /// Loads a wavetable from a WAV file, only the first channel is used.
+    pub fn from_wav(path: impl AsRef<Path>) -> Result<Self, WavetableError> {
+        let mut reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let channels = spec.channels as usize;
+
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => {
+                reader.samples::<f32>().step_by(channels).collect::<Result<_, _>>()?
+            }
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+                reader.samples::<i32>().step_by(channels)
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        Self::from_samples(&samples)
+    }
+
+    /// Creates a wavetable from raw samples. Anything that consists of whole `FRAME_SIZE` sample
+    /// frames (like Serum tables) is split into frames, anything else is treated as a single cycle.
+    pub fn from_samples(samples: &[f32]) -> Result<Self, WavetableError> {
+        if samples.is_empty() {
+            return Err(WavetableError::Empty);
+        }
+
+        let frames: Vec<Vec<f32>> = if samples.len() % FRAME_SIZE == 0 {
+            samples.chunks(FRAME_SIZE).map(|frame| frame.to_vec()).collect()
+        } else {
+            vec![resample(samples, FRAME_SIZE)]
+        };
+
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(FRAME_SIZE);
+        let ifft = planner.plan_fft_inverse(FRAME_SIZE);
+
+        let mut data = Vec::with_capacity(frames.len() * MIPMAP_LEVELS * FRAME_SIZE);
+        for frame in &frames {
+            let mut spectrum: Vec<Complex<f32>> = frame.iter()
+                .map(|&s| Complex::new(s, 0.0))
+                .collect();
+            fft.process(&mut spectrum);
+            // Remove the DC offset
+            spectrum[0] = Complex::new(0.0, 0.0);
+
+            for level in 0..MIPMAP_LEVELS {
+                let max_harmonic = (FRAME_SIZE / 2) >> level;
+                let mut band_limited = spectrum.clone();
+                for (bin, value) in band_limited.iter_mut().enumerate() {
+                    // Bins above `FRAME_SIZE / 2` mirror the negative frequencies
+                    if bin.min(FRAME_SIZE - bin) >= max_harmonic {
+                        *value = Complex::new(0.0, 0.0);
+                    }
+                }
+                ifft.process(&mut band_limited);
+                data.extend(band_limited.iter().map(|c| c.re / FRAME_SIZE as f32));
+            }
+        }
+
+        Ok(Self {
+            frame_count: frames.len(),
+            data,
+        })
+    }
+
+    /// Get the value at `phase` for a `position` between 0 (first frame) and 1 (last frame),
+    /// using the mipmap level that has no harmonics above the Nyquist frequency.
+    pub fn sample(&self, position: f32, phase: f32, phase_delta: f32) -> f32 {
+        let level = mipmap_level(phase_delta);
+
+        let frame_position = position.clamp(0.0, 1.0) * (self.frame_count - 1) as f32;
+        let frame = frame_position as usize;
+        let next_frame = (frame + 1).min(self.frame_count - 1);
+        let t = frame_position - frame as f32;
+
+        let current = self.read(frame, level, phase);
+        let next = self.read(next_frame, level, phase);
+        current + (next - current) * t
+    }
+
+    fn read(&self, frame: usize, level: usize, phase: f32) -> f32 {
+        let start = (frame * MIPMAP_LEVELS + level) * FRAME_SIZE;
+        let table = &self.data[start..start + FRAME_SIZE];
+
+        let position = phase * FRAME_SIZE as f32;
+        let index = position as usize % FRAME_SIZE;
+        let t = position - position.floor();
+        let current = table[index];
+        let next = table[(index + 1) % FRAME_SIZE];
+        current + (next - current) * t
+    }
+}
+
+fn mipmap_level(phase_delta: f32) -> usize {
+    let max_harmonic = (0.5 / phase_delta.max(f32::EPSILON)) as usize + 1;
+    let mut level = 0;
+    while level < MIPMAP_LEVELS - 1 && (FRAME_SIZE / 2) >> level > max_harmonic {
+        level += 1;
+    }
+    level
+}
+
+/// Linearly resample a single cycle to `size` samples
+fn resample(samples: &[f32], size: usize) -> Vec<f32> {
+    let ratio = samples.len() as f32 / size as f32;
+    (0..size).map(|i| {
+        let position = i as f32 * ratio;
+        let index = position as usize;
+        let t = position - index as f32;
+        let current = samples[index];
+        let next = samples[(index + 1) % samples.len()];
+        current + (next - current) * t
+    }).collect()
+}
+
+#[derive(Debug)]
+pub enum WavetableError {
+    Read(hound::Error),
+    Empty,
+}
+
+impl Display for WavetableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WavetableError::Read(e) => write!(f, "{e}"),
+            WavetableError::Empty => write!(f, "the file does not contain any samples"),
+        }
+    }
+}
+
+impl From<hound::Error> for WavetableError {
+    fn from(value: hound::Error) -> Self {
+        WavetableError::Read(value)
+    }
+}
+
+/// The loaded wavetable of every oscillator, which are loaded by the GUI and handed to the audio
+/// thread without locking.
+pub struct Wavetables {
+    tables: [SwapSlot<Option<Wavetable>>; OSCILLATOR_AMOUNT],
+}
+
+impl Wavetables {
+    pub fn new() -> Self {
+        Self {
+            tables: std::array::from_fn(|_| SwapSlot::new()),
+        }
+    }
+
+    pub fn set(&self, index: usize, table: Option<Wavetable>) {
+        self.tables[index].send(table);
+    }
+
+    /// Replace the `current` tables of the audio thread with the ones that were changed, returns
+    /// whether any table was replaced
+    pub fn receive(&self, current: &mut [Option<Wavetable>; OSCILLATOR_AMOUNT]) -> bool {
+        let mut received = false;
+        for (slot, table) in self.tables.iter().zip(current) {
+            received |= slot.receive(table);
+        }
+        received
+    }
+
+    /// Drop the tables that were replaced on the audio thread
+    pub fn collect_retired(&self) {
+        for slot in &self.tables {
+            slot.collect_retired();
+        }
+    }
+}
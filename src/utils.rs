@@ -1,15 +1,23 @@
-use crate::{ENVELOPE_AMOUNT, OSCILLATOR_AMOUNT};
+use crate::{ENVELOPE_AMOUNT, LFO_AMOUNT, OSCILLATOR_AMOUNT};
 
+pub mod f32x4;
 pub mod fixed_map;
+pub mod random;
+pub mod swap_slot;
 
-fn get_vector(size: usize) -> Vec<usize> {
-    (0..size).collect::<Vec<usize>>()
+/// The indices `0..N`, built without allocating so it can be used on the audio thread
+fn get_indices<const N: usize>() -> [usize; N] {
+    std::array::from_fn(|i| i)
 }
 
 pub fn get_oscillator_array() -> [usize; OSCILLATOR_AMOUNT] {
-    get_vector(OSCILLATOR_AMOUNT).try_into().unwrap()
+    get_indices()
 }
 
 pub fn get_envelope_array() -> [usize; ENVELOPE_AMOUNT] {
-    get_vector(ENVELOPE_AMOUNT).try_into().unwrap()
+    get_indices()
+}
+
+pub fn get_lfo_array() -> [usize; LFO_AMOUNT] {
+    get_indices()
 }
\ No newline at end of file
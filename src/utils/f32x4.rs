@@ -0,0 +1,126 @@
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+use wide::f32x4;
+
+/// The amount of values in a [`F32x4`]
+pub const LANES: usize = 4;
+
+/// Four values that are processed together, like the samples of four voices, so they share the
+/// work that is the same for every voice. The arithmetic goes through [`wide`], which uses SIMD
+/// instructions where the target has them, the lanes can still be read and written directly.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct F32x4(pub [f32; LANES]);
+
+impl F32x4 {
+    pub const ZERO: Self = Self([0.0; LANES]);
+
+    pub fn splat(value: f32) -> Self {
+        Self([value; LANES])
+    }
+
+    pub fn from_fn(f: impl FnMut(usize) -> f32) -> Self {
+        Self(std::array::from_fn(f))
+    }
+
+    pub fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    fn simd(self) -> f32x4 {
+        f32x4::new(self.0)
+    }
+
+    fn from_simd(value: f32x4) -> Self {
+        Self(value.to_array())
+    }
+
+    pub fn floor(self) -> Self {
+        Self::from_simd(self.simd().floor())
+    }
+
+    /// The part after the decimal point, which wraps positive phases to 0..1
+    pub fn fract(self) -> Self {
+        self - self.floor()
+    }
+
+    pub fn abs(self) -> Self {
+        Self::from_simd(self.simd().abs())
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::from_simd(self.simd().min(other.simd()))
+    }
+
+    pub fn exp(self) -> Self {
+        Self::from_simd(self.simd().exp())
+    }
+}
+
+impl Add for F32x4 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::from_simd(self.simd() + rhs.simd())
+    }
+}
+
+impl Add<f32> for F32x4 {
+    type Output = Self;
+
+    fn add(self, rhs: f32) -> Self {
+        self + Self::splat(rhs)
+    }
+}
+
+impl AddAssign for F32x4 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for F32x4 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::from_simd(self.simd() - rhs.simd())
+    }
+}
+
+impl Sub<f32> for F32x4 {
+    type Output = Self;
+
+    fn sub(self, rhs: f32) -> Self {
+        self - Self::splat(rhs)
+    }
+}
+
+impl Mul for F32x4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_simd(self.simd() * rhs.simd())
+    }
+}
+
+impl Mul<f32> for F32x4 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        self * Self::splat(rhs)
+    }
+}
+
+impl Div for F32x4 {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self::from_simd(self.simd() / rhs.simd())
+    }
+}
+
+impl Div<f32> for F32x4 {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self {
+        self / Self::splat(rhs)
+    }
+}
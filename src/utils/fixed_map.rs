@@ -1,6 +1,7 @@
 use std::hash::Hash;
 use indexmap::IndexMap;
 
+/// A map with a fixed maximum size, which keeps the insertion order of its items.
 pub struct FixedMap<K, V> {
     pub map: IndexMap<K, V>,
     capacity: usize,
@@ -17,9 +18,9 @@ impl<K, V> FixedMap<K, V>
     }
 
     /// Inserts an item, if the map is not at capacity. Otherwise do nothing.
-    /// TODO remove oldest values? (IndexMap is used instead of HashMap so this is possible)
+    /// Use [`Self::is_full`] and [`Self::remove_index`] to make room first.
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if self.map.len() >= self.capacity {
+        if self.is_full() {
             None
         } else {
             self.map.insert(key, value)
@@ -27,6 +28,20 @@ impl<K, V> FixedMap<K, V>
     }
 
     pub fn remove(&mut self, key: &K) -> Option<V> {
-        self.map.remove(key)
+        self.map.shift_remove(key)
     }
-}
\ No newline at end of file
+
+    /// Removes the item at `index`, where index 0 is the oldest item
+    pub fn remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.map.shift_remove_index(index)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.map.len() >= self.capacity
+    }
+
+    /// Change the capacity, this does not allocate so it should not exceed the initial capacity.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+}
@@ -0,0 +1,27 @@
+/// A small and fast pseudo random number generator (xorshift), which can be used on the audio
+/// thread
+#[derive(Clone, Copy)]
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    pub fn new(seed: u32) -> Self {
+        // The state can never become 0 when it does not start at 0
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Get a value from 0 (inclusive) to 1 (exclusive)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+}
@@ -0,0 +1,131 @@
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// Hands values from other threads to the audio thread, without locking or (de)allocating on the
+/// audio thread. Values that the audio thread replaced are dropped by the next [`Self::send`], or
+/// by [`Self::collect_retired`] on another thread.
+pub struct SwapSlot<T> {
+    /// The latest value that was not received yet
+    pending: AtomicPtr<Node<T>>,
+    /// The values that were replaced by [`Self::receive`], which still need to be dropped. This is
+    /// a linked list, so a new value can be received before the previous one was dropped.
+    retired: AtomicPtr<Node<T>>,
+}
+
+struct Node<T> {
+    value: T,
+    next: *mut Node<T>,
+}
+
+impl<T> SwapSlot<T> {
+    pub fn new() -> Self {
+        Self {
+            pending: AtomicPtr::new(null_mut()),
+            retired: AtomicPtr::new(null_mut()),
+        }
+    }
+
+    /// Send a new value, replacing any value that was not received yet.
+    /// This allocates, so it should not be called from the audio thread.
+    pub fn send(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node { value, next: null_mut() }));
+        drop_list(self.pending.swap(node, Ordering::AcqRel));
+        self.collect_retired();
+    }
+
+    /// Drop the values that were replaced on the audio thread
+    pub fn collect_retired(&self) {
+        // The whole list is taken at once, so the audio thread can keep adding to an empty list
+        drop_list(self.retired.swap(null_mut(), Ordering::AcqRel));
+    }
+
+    /// Replace `current` with the latest sent value, returns whether there was a new value
+    pub fn receive(&self, current: &mut T) -> bool {
+        let node = self.pending.swap(null_mut(), Ordering::AcqRel);
+        if node.is_null() {
+            return false;
+        }
+
+        // SAFETY: the pointer was created by `send` and is no longer reachable through `pending`
+        std::mem::swap(current, unsafe { &mut (*node).value });
+        self.retire(node);
+        true
+    }
+
+    /// Add a node to the retired list, without allocating
+    fn retire(&self, node: *mut Node<T>) {
+        let mut head = self.retired.load(Ordering::Acquire);
+        loop {
+            // SAFETY: the node is owned by the audio thread until it is in the list
+            unsafe { (*node).next = head; }
+            match self.retired.compare_exchange_weak(head, node, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+impl<T> Default for SwapSlot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for SwapSlot<T> {
+    fn drop(&mut self) {
+        drop_list(*self.pending.get_mut());
+        drop_list(*self.retired.get_mut());
+    }
+}
+
+// SAFETY: the values are only moved between threads, never shared
+unsafe impl<T: Send> Send for SwapSlot<T> {}
+unsafe impl<T: Send> Sync for SwapSlot<T> {}
+
+fn drop_list<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        // SAFETY: all non-null pointers in a slot were created by `Box::into_raw` and are owned
+        // by the slot
+        let boxed = unsafe { Box::from_raw(node) };
+        node = boxed.next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receives_every_send() {
+        let slot = SwapSlot::new();
+        let mut current = 0;
+        assert!(!slot.receive(&mut current));
+
+        slot.send(1);
+        assert!(slot.receive(&mut current));
+        assert_eq!(current, 1);
+
+        slot.send(2);
+        assert!(slot.receive(&mut current));
+        assert_eq!(current, 2);
+        assert!(!slot.receive(&mut current));
+    }
+
+    #[test]
+    fn receives_while_a_value_is_retired() {
+        let slot = SwapSlot::new();
+        let mut current = 0;
+        slot.send(1);
+        assert!(slot.receive(&mut current));
+
+        // A send that raced with the receive above, so the retired value was not collected yet
+        let node = Box::into_raw(Box::new(Node { value: 2, next: null_mut() }));
+        drop_list(slot.pending.swap(node, Ordering::AcqRel));
+        assert!(slot.receive(&mut current));
+        assert_eq!(current, 2);
+
+        slot.collect_retired();
+        assert!(slot.retired.load(Ordering::Acquire).is_null());
+    }
+}